@@ -94,6 +94,9 @@ function Show-Status {
     $memoryPExists = Test-Path (Join-Path $MemoryPDir "src/main.rs")
     $memoryPStatus = if ($memoryPExists) { "✅ Ready" } else { "❌ Missing" }
     Write-Host "║  MEMORY_P Server:      $memoryPStatus"
+    $health = try { Invoke-RestMethod -Uri "http://127.0.0.1:4040/health" -TimeoutSec 2 } catch { $null }
+    $healthStatus = if ($health) { "✅ Up ($($health.uptime_secs)s)" } else { "❌ Down" }
+    Write-Host "║  MEMORY_P /health:     $healthStatus"
 
     # Cache
     $cacheSize = 0
@@ -12,7 +12,11 @@ function mem {
 
     switch ($Command) {
         "status" {
-            Invoke-RestMethod -Uri $BaseUrl -Method Post -Body '{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"ultra_overview","arguments":{"path":"."}}}' -ContentType "application/json" | Select-Object -ExpandProperty result | Select-Object -ExpandProperty content | ForEach-Object { Write-Host $_.text -ForegroundColor Cyan }
+            $status = Invoke-RestMethod -Uri "http://127.0.0.1:4040/status" -Method Get
+            Write-Host "🟢 MEMORY_P v$($status.version) | uptime: $($status.uptime_secs)s | jobs: $($status.active_jobs) | cache: $($status.analysis_cache_entries)" -ForegroundColor Cyan
+            if ($status.last_error) {
+                Write-Host "⚠️ Último error ($($status.last_error.origin)): $($status.last_error.message)" -ForegroundColor Yellow
+            }
         }
         "analyze" {
             $path = Resolve-Path $Target
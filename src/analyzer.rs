@@ -27,6 +27,19 @@ pub struct FileAnalysis {
 pub struct CodeAnalyzer;
 
 impl CodeAnalyzer {
+    /// Archivos con análisis en caché (`/status`)
+    pub fn cache_entries() -> usize {
+        ANALYSIS_CACHE.len()
+    }
+
+    /// (aciertos, fallos) de `ANALYSIS_CACHE` desde el arranque (`/metrics`)
+    pub fn cache_stats() -> (u64, u64) {
+        (
+            CACHE_HITS.load(Ordering::Relaxed),
+            CACHE_MISSES.load(Ordering::Relaxed),
+        )
+    }
+
     /// Escanea un directorio recursivamente y retorna paths de archivos con extensión dada de forma paralela (Ripgrep Engine)
     pub fn scan_files(
         root_path: &str,
@@ -50,8 +63,8 @@ impl CodeAnalyzer {
         let files: Vec<PathBuf> = walker
             .into_iter()
             .filter_map(|e| e.ok())
-            .filter(|e| e.file_type().map_or(false, |ft| ft.is_file()))
-            .filter(|e| e.path().extension().map_or(false, |ext| ext == extension))
+            .filter(|e| e.file_type().is_some_and(|ft| ft.is_file()))
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == extension))
             .map(|e| e.path().to_path_buf())
             .collect();
 
@@ -78,9 +91,11 @@ impl CodeAnalyzer {
 
         if let Some(entry) = ANALYSIS_CACHE.get(&path_key) {
             if entry.0 == modified {
+                CACHE_HITS.fetch_add(1, Ordering::Relaxed);
                 return Ok(entry.1.clone());
             }
         }
+        CACHE_MISSES.fetch_add(1, Ordering::Relaxed);
 
         // ⚡ MMAP READ (Zero-copy I/O)
         let file = fs::File::open(file_path)?;
@@ -128,6 +143,10 @@ impl CodeAnalyzer {
 }
 
 use lazy_static::lazy_static;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static CACHE_HITS: AtomicU64 = AtomicU64::new(0);
+static CACHE_MISSES: AtomicU64 = AtomicU64::new(0);
 
 // use dashmap::DashMap; // REMOVED as SCC is used
 
@@ -141,7 +160,8 @@ lazy_static! {
     static ref RE_COMMENT: Regex = Regex::new(r"//").unwrap();
 
     // Complejidad
-    static ref RE_COMPLEX_IF: Regex = Regex::new(r"if\s*[({]").unwrap();
+    // `if cond {` (Rust, Go...) no abre paréntesis tras el `if`: se cuenta la palabra sola
+    static ref RE_COMPLEX_IF: Regex = Regex::new(r"\bif\b").unwrap();
     static ref RE_COMPLEX_ELSE: Regex = Regex::new(r"else").unwrap();
     static ref RE_COMPLEX_MATCH: Regex = Regex::new(r"match\s*").unwrap();
     static ref RE_COMPLEX_FOR: Regex = Regex::new(r"for\s+").unwrap();
@@ -235,10 +255,8 @@ fn detect_warnings(content: &str, file_path: &Path) -> Vec<String> {
                 warnings.push("📐 RUST: Vec sin with_capacity - optimización posible".into());
             }
         }
-        "go" => {
-            if content.contains("interface{}") {
-                warnings.push("⚠️ GO: Uso de interface{} vacía (Any). Tipado débil.".into());
-            }
+        "go" if content.contains("interface{}") => {
+            warnings.push("⚠️ GO: Uso de interface{} vacía (Any). Tipado débil.".into());
         }
         "bend" | "hvm" => {
             if content.contains("fold") && !content.contains("case") {
@@ -252,11 +270,8 @@ fn detect_warnings(content: &str, file_path: &Path) -> Vec<String> {
                 warnings.push("🚀 BEND: Código paralelizable - considerar run-cu para GPU".into());
             }
         }
-        "chpl" => {
-            if content.contains("forall") && !content.contains("with") {
-                warnings
-                    .push("⚠️ CHAPEL: 'forall' paralelo. Verificar data race o usar 'with'".into());
-            }
+        "chpl" if content.contains("forall") && !content.contains("with") => {
+            warnings.push("⚠️ CHAPEL: 'forall' paralelo. Verificar data race o usar 'with'".into());
         }
         "jl" => {
             // Julia analysis
@@ -324,4 +339,11 @@ mod tests {
         let complexity = estimate_complexity(content, 1.0);
         assert!(complexity > 2.0 && complexity < 10.0);
     }
+
+    #[test]
+    fn test_complex_if_counts_bare_keyword_only() {
+        // `if cond {` también cuenta; identificadores que contienen "if" no
+        assert_eq!(count_regex("if x > 0 { } else if (y) { }", &RE_COMPLEX_IF), 2);
+        assert_eq!(count_regex("elif notify(diff_if)", &RE_COMPLEX_IF), 0);
+    }
 }
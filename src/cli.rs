@@ -0,0 +1,305 @@
+//! cli.rs - Línea de comandos de MEMORY_P
+//! `memory_p serve [--bind IP] [--port N] [--transport stdio|http|both] [--config archivo]`.
+//! Sin subcomando equivale a `serve`; `--stdio` y `MCP_STDIO` se mantienen por compatibilidad
+//! con las configuraciones de clientes existentes.
+//! `analyze`, `repair`, `edit`, `workflow run` y `simulate` llaman al motor sin levantar el
+//! servidor (ver `commands.rs`); sus logs se limitan a advertencias salvo con `-v`.
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use tracing::level_filters::LevelFilter;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "memory_p",
+    version,
+    about = "Servidor MCP de análisis, edición y reparación masiva"
+)]
+pub struct Cli {
+    /// Archivo de configuración (por defecto memory_p.toml del directorio actual)
+    #[arg(long, global = true, value_name = "ARCHIVO")]
+    pub config: Option<PathBuf>,
+
+    /// Equivale a `serve --transport stdio` (compatibilidad)
+    #[arg(long, hide = true)]
+    pub stdio: bool,
+
+    /// Logs informativos también en los subcomandos del motor (`serve` siempre los muestra)
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Inicia el servidor MCP
+    Serve(ServeArgs),
+    /// Analiza un proyecto (LOC, complejidad, unsafe, unwrap, seguridad)
+    Analyze {
+        path: PathBuf,
+        /// Extensión de archivo a analizar
+        #[arg(long, default_value = "rs")]
+        extension: String,
+        /// No respetar .gitignore
+        #[arg(long)]
+        no_gitignore: bool,
+        /// Incluir archivos ocultos
+        #[arg(long)]
+        hidden: bool,
+        #[command(flatten)]
+        report: ReportArgs,
+    },
+    /// Repara en sitio imports duplicados, formato, EOL y espacios
+    Repair {
+        path: PathBuf,
+        #[arg(long, default_value = "rs")]
+        extension: String,
+        /// Escribir las reparaciones (por defecto solo se informa qué se repararía)
+        #[arg(long)]
+        apply: bool,
+        #[command(flatten)]
+        report: ReportArgs,
+    },
+    /// Aplica ediciones masivas (`{"changes": [...]}` o directamente el arreglo de cambios)
+    Edit {
+        /// Archivo JSON con las operaciones
+        #[arg(long, value_name = "ARCHIVO")]
+        ops: PathBuf,
+        /// Escribir los cambios (por defecto solo se simulan)
+        #[arg(long)]
+        apply: bool,
+        #[command(flatten)]
+        report: ReportArgs,
+    },
+    /// Workflows guardados (PAYLOAD_BANK/WORKFLOWS) o en cualquier archivo
+    #[command(subcommand)]
+    Workflow(WorkflowCommand),
+    /// Mega simulación por fase
+    Simulate {
+        /// 1=módulos, 2=paralelismo, 3=ecosystem
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=3))]
+        phase: u8,
+        /// Simulaciones por config
+        #[arg(long, default_value_t = 1000)]
+        iterations: usize,
+        /// Módulos para phase 1 (separados por coma)
+        #[arg(long, value_delimiter = ',')]
+        modules: Vec<String>,
+        /// Guardar el resultado en este archivo JSON
+        #[arg(long, value_name = "ARCHIVO")]
+        output: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WorkflowCommand {
+    /// Ejecuta un workflow: ruta a un JSON o nombre de uno guardado
+    Run {
+        file: String,
+        /// Escribir los cambios de los pasos Edit/Evolve (por defecto dry run)
+        #[arg(long)]
+        apply: bool,
+        /// Hilos de Rayon
+        #[arg(long)]
+        max_tasks: Option<usize>,
+        #[command(flatten)]
+        report: ReportArgs,
+    },
+}
+
+/// Formato de salida y umbrales que deciden el código de salida
+#[derive(Args, Debug, Clone)]
+pub struct ReportArgs {
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+    /// Salir con código 1 si hay más archivos con error que esto
+    #[arg(long, value_name = "N")]
+    pub max_errors: Option<usize>,
+    /// Salir con código 1 si hay más archivos con advertencias que esto
+    #[arg(long, value_name = "N")]
+    pub max_warnings: Option<usize>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Legible: un hallazgo por línea y un resumen
+    Text,
+    Json,
+}
+
+#[derive(Args, Debug, Default, Clone)]
+pub struct ServeArgs {
+    /// IP de escucha HTTP (por defecto `orchestrator.mcp_bind`)
+    #[arg(long, value_name = "IP")]
+    pub bind: Option<IpAddr>,
+
+    /// Puerto HTTP (por defecto `orchestrator.mcp_port`)
+    #[arg(long)]
+    pub port: Option<u16>,
+
+    /// Transporte: stdio, http o ambos (por defecto http, o stdio con `MCP_STDIO`)
+    #[arg(long, value_enum)]
+    pub transport: Option<Transport>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    Stdio,
+    Http,
+    /// HTTP en segundo plano y stdio; el proceso termina al cerrarse stdin
+    Both,
+}
+
+impl Cli {
+    /// Argumentos de `serve`, incluido el caso sin subcomando
+    pub fn serve_args(&self) -> ServeArgs {
+        let mut args = match &self.command {
+            Some(Command::Serve(args)) => args.clone(),
+            _ => ServeArgs::default(),
+        };
+        if args.transport.is_none() && (self.stdio || std::env::var("MCP_STDIO").is_ok()) {
+            args.transport = Some(Transport::Stdio);
+        }
+        args
+    }
+
+    /// Nivel de los logs a stderr: INFO al servir; WARN en los subcomandos salvo `-v`
+    pub fn log_level(&self) -> LevelFilter {
+        match &self.command {
+            None | Some(Command::Serve(_)) => LevelFilter::INFO,
+            Some(_) if self.verbose => LevelFilter::INFO,
+            Some(_) => LevelFilter::WARN,
+        }
+    }
+}
+
+impl ServeArgs {
+    pub fn transport(&self) -> Transport {
+        self.transport.unwrap_or(Transport::Http)
+    }
+
+    /// Dirección HTTP: la CLI pisa a `[orchestrator] mcp_bind` / `mcp_port`
+    pub fn address(
+        &self,
+        config: &crate::config::OrchestratorConfig,
+    ) -> Result<SocketAddr, String> {
+        let ip = match self.bind {
+            Some(ip) => ip,
+            None => config.mcp_bind.parse().map_err(|e| {
+                format!(
+                    "orchestrator.mcp_bind inválido ('{}'): {}",
+                    config.mcp_bind, e
+                )
+            })?,
+        };
+        Ok(SocketAddr::new(ip, self.port.unwrap_or(config.mcp_port)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OrchestratorConfig;
+
+    #[test]
+    fn test_serve_args_override_config() {
+        let config = OrchestratorConfig::default();
+
+        let cli = Cli::try_parse_from(["memory_p"]).unwrap();
+        let args = cli.serve_args();
+        assert_eq!(args.address(&config).unwrap().to_string(), "127.0.0.1:4040");
+
+        let cli = Cli::try_parse_from([
+            "memory_p",
+            "serve",
+            "--bind",
+            "0.0.0.0",
+            "--port",
+            "4141",
+            "--transport",
+            "both",
+            "--config",
+            "otro.toml",
+        ])
+        .unwrap();
+        let args = cli.serve_args();
+        assert_eq!(args.transport(), Transport::Both);
+        assert_eq!(args.address(&config).unwrap().to_string(), "0.0.0.0:4141");
+        assert_eq!(cli.config, Some(PathBuf::from("otro.toml")));
+
+        let cli = Cli::try_parse_from(["memory_p", "--stdio"]).unwrap();
+        assert_eq!(cli.serve_args().transport(), Transport::Stdio);
+
+        assert!(Cli::try_parse_from(["memory_p", "serve", "--transport", "tcp"]).is_err());
+    }
+
+    #[test]
+    fn test_engine_subcommands_log_warnings_only() {
+        let level = |args: &[&str]| Cli::try_parse_from(args).unwrap().log_level();
+        assert_eq!(level(&["memory_p"]), LevelFilter::INFO);
+        assert_eq!(level(&["memory_p", "serve"]), LevelFilter::INFO);
+        assert_eq!(level(&["memory_p", "analyze", "src"]), LevelFilter::WARN);
+        assert_eq!(
+            level(&["memory_p", "analyze", "src", "-v"]),
+            LevelFilter::INFO
+        );
+        assert_eq!(
+            level(&["memory_p", "-v", "repair", "src"]),
+            LevelFilter::INFO
+        );
+    }
+
+    #[test]
+    fn test_engine_subcommands_parse() {
+        let cli = Cli::try_parse_from([
+            "memory_p",
+            "analyze",
+            "src",
+            "--format",
+            "json",
+            "--max-errors",
+            "0",
+        ])
+        .unwrap();
+        let Some(Command::Analyze {
+            path,
+            extension,
+            report,
+            ..
+        }) = cli.command
+        else {
+            panic!("se esperaba analyze");
+        };
+        assert_eq!(path, PathBuf::from("src"));
+        assert_eq!(extension, "rs");
+        assert_eq!(report.format, Format::Json);
+        assert_eq!(report.max_errors, Some(0));
+        assert_eq!(report.max_warnings, None);
+
+        let cli =
+            Cli::try_parse_from(["memory_p", "workflow", "run", "fix_lib", "--apply"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Workflow(WorkflowCommand::Run { apply: true, .. }))
+        ));
+
+        let cli = Cli::try_parse_from(["memory_p", "repair", "src"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Repair { apply: false, .. })
+        ));
+
+        let cli = Cli::try_parse_from(["memory_p", "simulate", "--phase", "2"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Simulate { phase: 2, .. })
+        ));
+        assert!(Cli::try_parse_from(["memory_p", "simulate", "--phase", "4"]).is_err());
+        assert!(Cli::try_parse_from(["memory_p", "edit"]).is_err());
+    }
+}
@@ -0,0 +1,260 @@
+//! commands.rs - Subcomandos de la CLI que corren el motor sin servidor
+//! `analyze`, `repair`, `edit`, `workflow run` y `simulate` llaman directo a `parallel_engine`
+//! y `mega_simulator`. Código de salida: 0 = ok, 1 = hallazgos por encima de
+//! `--max-errors` / `--max-warnings`, 2 = el comando no pudo correr.
+
+use crate::analyzer::CodeAnalyzer;
+use crate::cli::{Command, Format, ReportArgs, WorkflowCommand};
+use crate::config::CONFIG;
+use crate::error::Result;
+use crate::mcp::models::{UltraEditRequest, UltraWorkflowRequest};
+use crate::mcp::tools;
+use crate::mega_simulator::{self, SimConfig};
+use crate::parallel_engine::{self, ProcessingResult, ProcessingStats, ProcessingStatus};
+use serde_json::{json, Value};
+use std::process::ExitCode;
+
+/// Hallazgos por encima del umbral pedido
+const EXIT_THRESHOLD: u8 = 1;
+/// Ruta, JSON o motor fallaron antes de producir un resultado
+const EXIT_ERROR: u8 = 2;
+
+pub fn run(command: Command) -> ExitCode {
+    match execute(command) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+fn execute(command: Command) -> Result<ExitCode> {
+    match command {
+        Command::Serve(_) => unreachable!("serve lo atiende main"),
+        Command::Analyze {
+            path,
+            extension,
+            no_gitignore,
+            hidden,
+            report,
+        } => {
+            let path = path.to_string_lossy();
+            let files = CodeAnalyzer::scan_files(&path, &extension, !no_gitignore, hidden)?;
+            let (results, stats) =
+                parallel_engine::ultra_analyze(&files, CONFIG.to_parallel_config())?;
+            print_batch(
+                &report,
+                format!("🔬 Analyze {}", path),
+                json!({ "path": path }),
+                &results,
+                &stats,
+            )
+        }
+        Command::Repair {
+            path,
+            extension,
+            apply,
+            report,
+        } => {
+            let dry_run = !apply;
+            let path = path.to_string_lossy();
+            let files = CodeAnalyzer::scan_files(&path, &extension, true, false)?;
+            let (results, stats) =
+                parallel_engine::ultra_repair(&files, CONFIG.to_parallel_config(), dry_run)?;
+            print_batch(
+                &report,
+                format!("🛠️ Repair {}{}", path, if dry_run { " [DRY]" } else { "" }),
+                json!({ "path": path, "dry_run": dry_run }),
+                &results,
+                &stats,
+            )
+        }
+        Command::Edit { ops, apply, report } => {
+            let mut request: Value = serde_json::from_str(&std::fs::read_to_string(&ops)?)?;
+            if request.is_array() {
+                request = json!({ "changes": request });
+            }
+            let request = serde_json::from_value::<UltraEditRequest>(request)?;
+            let (results, stats) =
+                parallel_engine::ultra_edit(&request.changes, CONFIG.to_parallel_config(), !apply)?;
+            print_batch(
+                &report,
+                format!("✏️ Edit {}", if apply { "[APPLIED]" } else { "[DRY]" }),
+                json!({ "dry_run": !apply }),
+                &results,
+                &stats,
+            )
+        }
+        Command::Workflow(WorkflowCommand::Run {
+            file,
+            apply,
+            max_tasks,
+            report,
+        }) => {
+            let mut request =
+                serde_json::from_value::<UltraWorkflowRequest>(tools::load_workflow(&file)?)?;
+            request.dry_run = Some(!apply);
+            let mut config = CONFIG.to_parallel_config();
+            if let Some(max_tasks) = max_tasks.or(request.max_tasks) {
+                config.max_threads = max_tasks;
+            }
+            let (results, stats) = parallel_engine::ultra_workflow(&request, config)?;
+            print_batch(
+                &report,
+                format!("🌊 Workflow {} ({} pasos)", file, request.steps.len()),
+                json!({ "steps": request.steps.len(), "dry_run": !apply }),
+                &results,
+                &stats,
+            )
+        }
+        Command::Simulate {
+            phase,
+            iterations,
+            modules,
+            output,
+            format,
+        } => {
+            let result = mega_simulator::run_mega_simulation(SimConfig {
+                phase,
+                iterations,
+                modules,
+                ..Default::default()
+            })?;
+            if let Some(output) = &output {
+                mega_simulator::save_results(&result, output)?;
+            }
+            match format {
+                Format::Json => println!("{}", serde_json::to_string_pretty(&result)?),
+                Format::Text => {
+                    println!(
+                        "🌀 Phase {} | ⏱️ {}ms | 📊 {}/{} sims",
+                        result.phase, result.duration_ms, result.completed, result.total_sims
+                    );
+                    for i in &result.improvements {
+                        println!(
+                            "📈 {} ({}): {:.1}% improvement",
+                            i.target, i.metric, i.improvement_pct
+                        );
+                    }
+                }
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+/// Imprime el lote en el formato pedido y decide el código de salida por los umbrales
+fn print_batch(
+    report: &ReportArgs,
+    title: String,
+    mut structured: Value,
+    results: &[ProcessingResult],
+    stats: &ProcessingStats,
+) -> Result<ExitCode> {
+    match report.format {
+        Format::Json => {
+            structured["stats"] = serde_json::to_value(stats)?;
+            structured["results"] = serde_json::to_value(results)?;
+            println!("{}", serde_json::to_string_pretty(&structured)?);
+        }
+        Format::Text => {
+            for r in results
+                .iter()
+                .filter(|r| r.status != ProcessingStatus::Success)
+            {
+                println!("{} {}: {}", icon(r.status), r.path, r.findings.join(" | "));
+            }
+            println!(
+                "{}: {} archivos | ✅ {} | ⚠️ {} | ❌ {} | ⏭️ {} | {}ms",
+                title,
+                stats.total_files,
+                count(results, ProcessingStatus::Success),
+                count(results, ProcessingStatus::Warning),
+                count(results, ProcessingStatus::Error),
+                count(results, ProcessingStatus::Skipped),
+                stats.total_duration_ms
+            );
+        }
+    }
+
+    let exceeded = exceeded_thresholds(report, results);
+    for reason in &exceeded {
+        eprintln!("❌ {}", reason);
+    }
+    Ok(if exceeded.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_THRESHOLD)
+    })
+}
+
+/// Umbrales superados. Se cuenta sobre los resultados y no sobre `ProcessingStats`,
+/// que no todos los motores (edit, workflow) llenan por completo.
+fn exceeded_thresholds(report: &ReportArgs, results: &[ProcessingResult]) -> Vec<String> {
+    [
+        ("errores", ProcessingStatus::Error, report.max_errors),
+        (
+            "advertencias",
+            ProcessingStatus::Warning,
+            report.max_warnings,
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(label, status, max)| {
+        let found = count(results, status);
+        max.filter(|max| found > *max)
+            .map(|max| format!("{} archivos con {} (máximo {})", found, label, max))
+    })
+    .collect()
+}
+
+fn count(results: &[ProcessingResult], status: ProcessingStatus) -> usize {
+    results.iter().filter(|r| r.status == status).count()
+}
+
+fn icon(status: ProcessingStatus) -> &'static str {
+    match status {
+        ProcessingStatus::Success => "✅",
+        ProcessingStatus::Warning => "⚠️",
+        ProcessingStatus::Error => "❌",
+        ProcessingStatus::Skipped => "⏭️",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(status: ProcessingStatus) -> ProcessingResult {
+        ProcessingResult {
+            path: "x.rs".to_string(),
+            status,
+            findings: vec![],
+        }
+    }
+
+    #[test]
+    fn test_thresholds_only_fail_when_exceeded() {
+        let results = vec![
+            result(ProcessingStatus::Error),
+            result(ProcessingStatus::Warning),
+            result(ProcessingStatus::Warning),
+        ];
+        let report = |max_errors, max_warnings| ReportArgs {
+            format: Format::Text,
+            max_errors,
+            max_warnings,
+        };
+
+        assert!(exceeded_thresholds(&report(None, None), &results).is_empty());
+        assert!(exceeded_thresholds(&report(Some(1), Some(2)), &results).is_empty());
+        assert_eq!(
+            exceeded_thresholds(&report(Some(0), Some(1)), &results),
+            vec![
+                "1 archivos con errores (máximo 0)",
+                "2 archivos con advertencias (máximo 1)"
+            ]
+        );
+    }
+}
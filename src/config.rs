@@ -1,26 +1,42 @@
-use serde::Deserialize;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
 
 /// Configuración principal de MEMORY_P
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct AppConfig {
     pub parallelism: ParallelismConfig,
     #[serde(default)]
     pub advanced: AdvancedConfig,
     #[serde(default)]
     pub orchestrator: OrchestratorConfig,
+    #[serde(default)]
+    pub workspace: WorkspaceConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub http: HttpConfig,
+    #[serde(default)]
+    pub limits: LimitsConfig,
+    #[serde(default)]
+    pub jobs: JobsConfig,
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
 }
 
 /// Configuración de paralelismo (Rayon + Tokio)
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ParallelismConfig {
     pub threads: usize,
     pub batch_size: usize,
 }
 
-/// Configuración avanzada de I/O y rendimiento
+/// Configuración avanzada de I/O y rendimiento (los campos omitidos toman su default)
 #[allow(dead_code)]
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
 pub struct AdvancedConfig {
     /// Timeout para operaciones de archivo (ms)
     pub file_timeout_ms: u64,
@@ -32,16 +48,135 @@ pub struct AdvancedConfig {
     pub enable_scc_cache: bool,
 }
 
-/// Configuración del orquestador Julia
+/// Configuración del orquestador Julia (y dirección del servidor MCP HTTP)
 #[allow(dead_code)]
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct OrchestratorConfig {
     pub auto_analyze: bool,
+    /// IP en la que escucha el servidor HTTP (`serve --bind` la pisa)
+    #[serde(default = "default_mcp_bind")]
+    pub mcp_bind: String,
+    /// Puerto del servidor HTTP (`serve --port` lo pisa)
     pub mcp_port: u16,
     pub bend_enabled: bool,
     pub report_format: String,
 }
 
+/// Raíces de trabajo para clientes que no declaran `roots` (y la API HTTP heredada)
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct WorkspaceConfig {
+    /// Directorios permitidos; vacío = directorio actual del servidor
+    #[serde(default)]
+    pub roots: Vec<String>,
+}
+
+/// Orígenes de navegador aceptados por el servidor HTTP (cabecera `Origin`)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Orígenes exactos permitidos, p.ej. `https://tools.example.com`. `"null"` (`file://`) es
+    /// opcional: cualquier web puede enviarlo desde un iframe sandbox
+    pub allowed_origins: Vec<String>,
+    /// Aceptar `http://localhost`, `127.0.0.1` y `[::1]` en cualquier puerto
+    pub allow_localhost: bool,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allow_localhost: true,
+        }
+    }
+}
+
+/// Control de admisión de `tools/call` (ver `mcp::admission`)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct LimitsConfig {
+    /// Tools corriendo a la vez; cada una arma su propio pool de rayon
+    pub max_concurrent_jobs: usize,
+    /// Llamadas esperando turno; con la cola llena se rechaza al instante
+    pub max_queued: usize,
+    /// Espera máxima en la cola (ms)
+    pub queue_timeout_ms: u64,
+    /// `tools/call` por minuto por cliente (token, sesión o stdio); 0 = sin límite
+    pub rate_per_minute: u32,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_jobs: 2,
+            max_queued: 16,
+            queue_timeout_ms: 30_000,
+            rate_per_minute: 60,
+        }
+    }
+}
+
+/// Trabajos asíncronos (`/jobs`)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct JobsConfig {
+    /// Segundos que se guarda un job terminado (estado y resultado)
+    pub ttl_secs: u64,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self { ttl_secs: 3600 }
+    }
+}
+
+/// Apagado ordenado con Ctrl+C / SIGTERM (ver `mcp::shutdown`)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct ShutdownConfig {
+    /// Segundos que se espera a las tools y jobs en curso antes de cancelarlos
+    pub drain_timeout_secs: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            drain_timeout_secs: 30,
+        }
+    }
+}
+
+/// Tokens Bearer del transporte HTTP; sin tokens (ni `MEMORY_P_TOKEN`) no se exige autenticación
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct AuthConfig {
+    #[serde(default)]
+    pub tokens: Vec<TokenConfig>,
+}
+
+/// Un token con sus permisos. El secreto va en `token` o en la variable de entorno `env`;
+/// nunca se serializa (`/status` muestra la configuración efectiva) ni sale en `Debug`.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct TokenConfig {
+    /// Nombre para los logs
+    pub name: String,
+    #[serde(default, skip_serializing)]
+    pub token: Option<String>,
+    /// Variable de entorno con el token
+    #[serde(default)]
+    pub env: Option<String>,
+    pub scopes: Vec<crate::mcp::auth::Scope>,
+}
+
+impl std::fmt::Debug for TokenConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenConfig")
+            .field("name", &self.name)
+            .field("token", &self.token.as_ref().map(|_| "<redactado>"))
+            .field("env", &self.env)
+            .field("scopes", &self.scopes)
+            .finish()
+    }
+}
+
 impl Default for AdvancedConfig {
     fn default() -> Self {
         Self {
@@ -57,6 +192,7 @@ impl Default for OrchestratorConfig {
     fn default() -> Self {
         Self {
             auto_analyze: true,
+            mcp_bind: default_mcp_bind(),
             mcp_port: 4040,
             bend_enabled: true,
             report_format: "json".to_string(),
@@ -64,14 +200,47 @@ impl Default for OrchestratorConfig {
     }
 }
 
+fn default_mcp_bind() -> String {
+    "127.0.0.1".to_string()
+}
+
+/// Archivo de configuración por defecto (relativo al directorio actual)
+pub const DEFAULT_CONFIG_PATH: &str = "memory_p.toml";
+
+lazy_static! {
+    /// Archivo elegido con `--config`; se fija al arrancar, antes del primer `load()`
+    static ref CONFIG_PATH: RwLock<PathBuf> = RwLock::new(PathBuf::from(DEFAULT_CONFIG_PATH));
+}
+
+/// Usa otro archivo de configuración (CLI `--config`). A diferencia del archivo por
+/// defecto, uno pedido explícitamente debe existir y parsear: si no, el arranque falla.
+pub fn set_path(path: &Path) -> Result<(), String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("No se pudo leer {}: {}", path.display(), e))?;
+    toml::from_str::<AppConfig>(&content)
+        .map_err(|e| format!("Configuración inválida en {}: {}", path.display(), e))?;
+    if let Ok(mut current) = CONFIG_PATH.write() {
+        *current = path.to_path_buf();
+    }
+    Ok(())
+}
+
+pub fn path() -> PathBuf {
+    CONFIG_PATH
+        .read()
+        .map(|p| p.clone())
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_CONFIG_PATH))
+}
+
 impl AppConfig {
-    /// Carga configuración desde memory_p.toml con soporte para todas las secciones
+    /// Carga configuración desde memory_p.toml (o `--config`) con soporte para todas las secciones
     pub fn load() -> Self {
-        let path = "memory_p.toml";
-        if let Ok(content) = fs::read_to_string(path) {
+        let file = path();
+        let path = file.display();
+        if let Ok(content) = fs::read_to_string(&file) {
             match toml::from_str(&content) {
                 Ok(cfg) => {
-                    tracing::info!("⚡ Configuración cargada de {}: {:?}", path, cfg);
+                    tracing::info!("⚡ Configuración cargada de {}", path);
                     return cfg;
                 }
                 Err(e) => {
@@ -88,6 +257,12 @@ impl AppConfig {
             },
             advanced: AdvancedConfig::default(),
             orchestrator: OrchestratorConfig::default(),
+            workspace: WorkspaceConfig::default(),
+            auth: AuthConfig::default(),
+            http: HttpConfig::default(),
+            limits: LimitsConfig::default(),
+            jobs: JobsConfig::default(),
+            shutdown: ShutdownConfig::default(),
         }
     }
 
@@ -100,12 +275,33 @@ impl AppConfig {
             _file_timeout_ms: self.advanced.file_timeout_ms,
             _continue_on_error: true,
             _large_file_threshold: self.advanced.large_file_threshold,
+            monitor: None,
         }
     }
 }
 
-/// Singleton global de configuración (Lock-free con lazy_static)
-use lazy_static::lazy_static;
+// Singleton global de configuración (Lock-free con lazy_static)
 lazy_static! {
     pub static ref CONFIG: AppConfig = AppConfig::load();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_secret_not_in_debug() {
+        let config: AuthConfig = toml::from_str(
+            r#"
+            [[tokens]]
+            name = "ci"
+            token = "s3cr3t-value"
+            scopes = ["read"]
+            "#,
+        )
+        .unwrap();
+        let shown = format!("{:?}", config);
+        assert!(shown.contains("ci"));
+        assert!(!shown.contains("s3cr3t-value"));
+    }
+}
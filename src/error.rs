@@ -25,6 +25,35 @@ pub enum MemoryPError {
     #[error("Argumentos inválidos: {0}")]
     InvalidParams(String),
 
+    /// `arguments` que no cumplen el `inputSchema` de la tool: (JSON Pointer, motivo)
+    #[error("Argumentos inválidos: {}", describe_violations(.0))]
+    SchemaViolations(Vec<(String, String)>),
+
+    #[error("Error de parseo JSON-RPC: {0}")]
+    Parse(String),
+
+    #[error("Solicitud JSON-RPC inválida: {0}")]
+    InvalidRequest(String),
+
+    #[error("Método no encontrado: {0}")]
+    MethodNotFound(String),
+
+    #[error("Ruta fuera de las raíces permitidas: {0}")]
+    PathNotAllowed(PathBuf),
+
+    #[error("No autenticado: {0}")]
+    Unauthorized(String),
+
+    #[error("Permiso denegado: {0}")]
+    Forbidden(String),
+
+    /// Admisión rechazada (cola llena, espera agotada o límite de tasa)
+    #[error("Servidor ocupado, reintenta en {retry_after_secs}s: {reason}")]
+    Busy {
+        reason: String,
+        retry_after_secs: u64,
+    },
+
     #[error("Unsupported template: {0}")]
     Unsupported(String),
 
@@ -46,6 +75,115 @@ pub enum MemoryPError {
 /// Alias para Result<T, MemoryPError>
 pub type Result<T> = std::result::Result<T, MemoryPError>;
 
+// Códigos JSON-RPC 2.0 estándar
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+// Códigos propios del servidor (rango reservado -32000..-32099)
+pub const INVALID_DIRECTORY: i64 = -32001;
+pub const RESOURCE_NOT_FOUND: i64 = -32002;
+pub const UNSUPPORTED: i64 = -32003;
+pub const LOCK_ERROR: i64 = -32004;
+pub const ANALYSIS_ERROR: i64 = -32005;
+pub const PATH_NOT_ALLOWED: i64 = -32006;
+pub const UNAUTHORIZED: i64 = -32007;
+pub const FORBIDDEN: i64 = -32008;
+pub const SERVER_BUSY: i64 = -32009;
+
+impl MemoryPError {
+    /// Código JSON-RPC con el que se reporta este error
+    pub fn rpc_code(&self) -> i64 {
+        match self {
+            MemoryPError::Parse(_) => PARSE_ERROR,
+            MemoryPError::InvalidRequest(_) => INVALID_REQUEST,
+            MemoryPError::MethodNotFound(_) => METHOD_NOT_FOUND,
+            MemoryPError::InvalidParams(_)
+            | MemoryPError::SchemaViolations(_)
+            | MemoryPError::Regex(_)
+            | MemoryPError::Json(_) => INVALID_PARAMS,
+            MemoryPError::InvalidDirectory(_) => INVALID_DIRECTORY,
+            MemoryPError::FileNotFound(_) => RESOURCE_NOT_FOUND,
+            MemoryPError::Unsupported(_) => UNSUPPORTED,
+            MemoryPError::LockError(_) => LOCK_ERROR,
+            MemoryPError::AnalysisError(_) => ANALYSIS_ERROR,
+            MemoryPError::PathNotAllowed(_) => PATH_NOT_ALLOWED,
+            MemoryPError::Unauthorized(_) => UNAUTHORIZED,
+            MemoryPError::Forbidden(_) => FORBIDDEN,
+            MemoryPError::Busy { .. } => SERVER_BUSY,
+            MemoryPError::Io(_) | MemoryPError::ParallelError(_) | MemoryPError::Other(_) => {
+                INTERNAL_ERROR
+            }
+        }
+    }
+
+    /// Nombre estable de la variante (campo `data.kind` del error JSON-RPC)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MemoryPError::Io(_) => "Io",
+            MemoryPError::FileNotFound(_) => "FileNotFound",
+            MemoryPError::InvalidDirectory(_) => "InvalidDirectory",
+            MemoryPError::Regex(_) => "Regex",
+            MemoryPError::Json(_) => "Json",
+            MemoryPError::InvalidParams(_) => "InvalidParams",
+            MemoryPError::SchemaViolations(_) => "SchemaViolations",
+            MemoryPError::Parse(_) => "Parse",
+            MemoryPError::InvalidRequest(_) => "InvalidRequest",
+            MemoryPError::MethodNotFound(_) => "MethodNotFound",
+            MemoryPError::PathNotAllowed(_) => "PathNotAllowed",
+            MemoryPError::Unauthorized(_) => "Unauthorized",
+            MemoryPError::Forbidden(_) => "Forbidden",
+            MemoryPError::Busy { .. } => "Busy",
+            MemoryPError::Unsupported(_) => "Unsupported",
+            MemoryPError::ParallelError(_) => "ParallelError",
+            MemoryPError::LockError(_) => "LockError",
+            MemoryPError::AnalysisError(_) => "AnalysisError",
+            MemoryPError::Other(_) => "Other",
+        }
+    }
+
+    /// Datos estructurados para el campo `data` del error JSON-RPC
+    pub fn rpc_data(&self) -> serde_json::Value {
+        let mut data = serde_json::json!({ "kind": self.kind() });
+        match self {
+            MemoryPError::Io(e) => data["ioKind"] = format!("{:?}", e.kind()).into(),
+            MemoryPError::FileNotFound(path) | MemoryPError::PathNotAllowed(path) => {
+                data["path"] = path.display().to_string().into()
+            }
+            MemoryPError::InvalidDirectory(path) => data["path"] = path.clone().into(),
+            MemoryPError::Unsupported(what) => data["unsupported"] = what.clone().into(),
+            MemoryPError::Json(e) => {
+                data["line"] = e.line().into();
+                data["column"] = e.column().into();
+            }
+            MemoryPError::MethodNotFound(method) => data["method"] = method.clone().into(),
+            MemoryPError::Busy {
+                retry_after_secs, ..
+            } => data["retryAfter"] = (*retry_after_secs).into(),
+            MemoryPError::SchemaViolations(violations) => {
+                data["errors"] = violations
+                    .iter()
+                    .map(|(pointer, message)| {
+                        serde_json::json!({ "pointer": pointer, "message": message })
+                    })
+                    .collect();
+            }
+            _ => {}
+        }
+        data
+    }
+}
+
+fn describe_violations(violations: &[(String, String)]) -> String {
+    violations
+        .iter()
+        .map(|(pointer, message)| format!("{}: {}", pointer, message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 impl From<String> for MemoryPError {
     fn from(s: String) -> Self {
         MemoryPError::Other(s)
@@ -68,6 +206,23 @@ mod tests {
         assert!(err.to_string().contains("test.rs"));
     }
 
+    #[test]
+    fn test_rpc_codes() {
+        assert_eq!(
+            MemoryPError::MethodNotFound("x".into()).rpc_code(),
+            METHOD_NOT_FOUND
+        );
+        assert_eq!(
+            MemoryPError::InvalidParams("x".into()).rpc_code(),
+            INVALID_PARAMS
+        );
+        assert_eq!(MemoryPError::Other("x".into()).rpc_code(), INTERNAL_ERROR);
+        let err = MemoryPError::FileNotFound(PathBuf::from("a.rs"));
+        assert_eq!(err.rpc_code(), RESOURCE_NOT_FOUND);
+        assert_eq!(err.rpc_data()["kind"], "FileNotFound");
+        assert_eq!(err.rpc_data()["path"], "a.rs");
+    }
+
     #[test]
     fn test_error_conversion_from_string() {
         let err: MemoryPError = "test error".into();
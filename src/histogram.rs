@@ -0,0 +1,92 @@
+//! histogram.rs - Histograma de latencias sin locks
+//! Lo llena el motor (`JobMonitor::file_latency`) y lo expone `/metrics`; vive aparte para que
+//! `parallel_engine` no dependa de la capa MCP.
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Límites (segundos) de la latencia por archivo
+pub const FILE_BUCKETS: &[f64] = &[0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0];
+
+/// Histograma acumulativo sin locks: un contador por límite más `+Inf`
+#[derive(Debug)]
+pub struct Histogram {
+    bounds: &'static [f64],
+    counts: Vec<AtomicU64>,
+    sum_micros: AtomicU64,
+}
+
+impl Default for Histogram {
+    /// Con los límites de la latencia por archivo (el uso de `JobMonitor`)
+    fn default() -> Self {
+        Self::new(FILE_BUCKETS)
+    }
+}
+
+impl Histogram {
+    pub fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe(&self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        let bucket = self
+            .bounds
+            .iter()
+            .position(|&bound| secs <= bound)
+            .unwrap_or(self.bounds.len());
+        self.counts[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Suma las observaciones de otro histograma con los mismos límites
+    pub fn absorb(&self, other: &Histogram) {
+        for (mine, theirs) in self.counts.iter().zip(&other.counts) {
+            mine.fetch_add(theirs.load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+        self.sum_micros
+            .fetch_add(other.sum_micros.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    pub fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let mut cumulative = 0;
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            cumulative += count.load(Ordering::Relaxed);
+            let _ = writeln!(out, "{name}_bucket{{{labels},le=\"{bound}\"}} {cumulative}");
+        }
+        cumulative += self.counts[self.bounds.len()].load(Ordering::Relaxed);
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1e6;
+        let _ = writeln!(out, "{name}_bucket{{{labels},le=\"+Inf\"}} {cumulative}");
+        let _ = writeln!(out, "{name}_sum{{{labels}}} {sum}");
+        let _ = writeln!(out, "{name}_count{{{labels}}} {cumulative}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_is_cumulative() {
+        let histogram = Histogram::new(&[0.001, 0.1]);
+        histogram.observe(Duration::from_micros(500));
+        histogram.observe(Duration::from_millis(50));
+        histogram.observe(Duration::from_secs(2));
+
+        let merged = Histogram::new(&[0.001, 0.1]);
+        merged.absorb(&histogram);
+        let mut out = String::new();
+        merged.render(&mut out, "m", "tool=\"t\"");
+        assert!(out.contains("m_bucket{tool=\"t\",le=\"0.001\"} 1\n"));
+        assert!(out.contains("m_bucket{tool=\"t\",le=\"0.1\"} 2\n"));
+        assert!(out.contains("m_bucket{tool=\"t\",le=\"+Inf\"} 3\n"));
+        assert!(out.contains("m_sum{tool=\"t\"} 2.0505\n"));
+        assert!(out.contains("m_count{tool=\"t\"} 3\n"));
+    }
+}
@@ -8,18 +8,22 @@
 //! - Endpoints: /analyze, /edit, /repair
 
 use axum::Router;
+use clap::Parser;
 use mimalloc::MiMalloc;
 use std::net::SocketAddr;
+use std::process::ExitCode;
 use tokio::net::TcpListener;
-use tracing_subscriber;
 
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
 mod accelerator_bridge;
 mod analyzer;
+mod cli;
+mod commands;
 mod config;
 mod error;
+mod histogram;
 mod mcp;
 mod mcp_api;
 mod mega_simulator; // 3-phase mega simulation engine
@@ -28,75 +32,264 @@ mod simulation_engine; // Legacy native engine
 mod workspace;
 
 #[tokio::main]
-async fn main() {
-    let args: Vec<String> = std::env::args().collect();
+async fn main() -> ExitCode {
+    let mut cli = cli::Cli::parse();
+    init_tracing(cli.log_level());
 
-    if args.contains(&"--stdio".to_string()) || std::env::var("MCP_STDIO").is_ok() {
-        // En modo stdio, NO enviamos nada a stdout excepto JSON puro.
-        if let Err(e) = mcp_stdio_mode().await {
-            eprintln!("❌ Error en modo stdio: {}", e);
+    // Antes de cualquier acceso a CONFIG
+    if let Some(path) = &cli.config {
+        if let Err(e) = config::set_path(path) {
+            eprintln!("❌ {}", e);
+            return ExitCode::FAILURE;
         }
-    } else {
-        // Inicializar logging solo en modo HTTP
-        tracing_subscriber::fmt()
-            .with_writer(std::io::stderr)
-            .with_max_level(tracing::Level::INFO)
-            .with_target(true)
-            .init();
+    }
+
+    // analyze, repair, edit, workflow, simulate: el motor directo, sin servidor
+    if let Some(command) = cli
+        .command
+        .take_if(|c| !matches!(c, cli::Command::Serve(_)))
+    {
+        return tokio::task::spawn_blocking(move || commands::run(command))
+            .await
+            .unwrap_or(ExitCode::FAILURE);
+    }
+
+    mcp::status::mark_started();
+    mcp::tools::install_builtin();
+
+    let serve = cli.serve_args();
+    let addr = match serve.address(&config::CONFIG.orchestrator) {
+        Ok(addr) => addr,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Un solo par de watchers aunque corran ambos transportes
+    mcp::resources::spawn_watcher();
+    mcp::prompts::spawn_watcher();
+    mcp::shutdown::spawn_listener();
+
+    // En modo stdio, NO enviamos nada a stdout excepto JSON puro.
+    let result = match serve.transport() {
+        cli::Transport::Stdio => mcp_stdio_mode().await,
+        cli::Transport::Http => http_server_mode(addr).await,
+        cli::Transport::Both => {
+            // El puerto se toma antes de leer stdin: si está ocupado, falla el arranque.
+            // Si un transporte termina (p.ej. fin de stdin), el otro se apaga en orden.
+            match bind(addr).await {
+                Ok(listener) => {
+                    let (http, stdio) = tokio::join!(
+                        async {
+                            let result = serve_http(listener).await;
+                            mcp::shutdown::trigger("HTTP detenido");
+                            result
+                        },
+                        async {
+                            let result = mcp_stdio_mode().await;
+                            mcp::shutdown::trigger("fin de stdin");
+                            result
+                        },
+                    );
+                    http.and(stdio)
+                }
+                Err(e) => Err(e),
+            }
+        }
+    };
 
-        if let Err(e) = http_server_mode().await {
-            tracing::error!("❌ Error en servidor HTTP: {}", e);
+    match result {
+        Ok(()) if mcp::shutdown::is_shutting_down() => mcp::shutdown::exit(),
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            ExitCode::FAILURE
         }
     }
 }
 
-async fn http_server_mode() -> crate::error::Result<()> {
+/// Logs a stderr (stdout queda para JSON-RPC en stdio) y al cliente MCP vía `notifications/message`
+fn init_tracing(level: tracing_subscriber::filter::LevelFilter) {
+    use tracing_subscriber::prelude::*;
+
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_target(true)
+                .with_filter(level),
+        )
+        .with(mcp::logging::McpLogLayer)
+        .init();
+}
+
+async fn http_server_mode(addr: SocketAddr) -> crate::error::Result<()> {
+    let listener = bind(addr).await?;
+    serve_http(listener).await
+}
+
+/// Toma el puerto; si ya está en uso, el error lo dice para poder elegir otro
+async fn bind(addr: SocketAddr) -> crate::error::Result<TcpListener> {
+    TcpListener::bind(addr).await.map_err(|e| {
+        let hint = if e.kind() == std::io::ErrorKind::AddrInUse {
+            " (¿otra instancia usa el puerto? elige otro con --port)"
+        } else {
+            ""
+        };
+        crate::error::MemoryPError::Other(format!("No se pudo escuchar en {}: {}{}", addr, e, hint))
+    })
+}
+
+async fn serve_http(listener: TcpListener) -> crate::error::Result<()> {
+    mcp::session::spawn_reaper();
+    mcp::jobs::spawn_reaper();
+    mcp::auth::init();
+
     // Construir router
     let app = Router::new().merge(mcp_api::routes()).fallback(error_404);
 
-    let addr = SocketAddr::from(([127, 0, 0, 1], 4040));
-
+    let addr = listener
+        .local_addr()
+        .map_err(crate::error::MemoryPError::Io)?;
     tracing::info!("🚀 MCP Toolkit HTTP iniciando");
     tracing::info!(
-        "📡 Escuchando en http://{}:{} (MCP Protocol 2024-11-05)",
+        "📡 Escuchando en http://{}:{} (MCP {})",
         addr.ip(),
-        addr.port()
+        addr.port(),
+        mcp::protocol::SUPPORTED_VERSIONS.join(", ")
     );
 
-    let listener = TcpListener::bind(addr)
-        .await
-        .map_err(|e| crate::error::MemoryPError::Io(e))?;
-
+    // Tras la señal: no más conexiones, se esperan los requests en curso y luego los jobs
     axum::serve(listener, app)
+        .with_graceful_shutdown(mcp::shutdown::requested())
         .await
-        .map_err(|e| crate::error::MemoryPError::Io(e))?;
+        .map_err(crate::error::MemoryPError::Io)?;
+    mcp::shutdown::drain().await;
+    tracing::info!("✅ Servidor HTTP detenido");
 
     Ok(())
 }
 
+/// Requests de stdio atendidos a la vez; con todos ocupados se deja de leer stdin
+const STDIO_MAX_IN_FLIGHT: usize = 8;
+
 async fn mcp_stdio_mode() -> crate::error::Result<()> {
-    use crate::mcp::models::JsonRpcRequest;
-    use crate::mcp_api::mcp_json_rpc_handler;
+    tracing::info!("✅ MEMORY_P MCP Stdio listo");
+    serve_stdio(
+        tokio::io::BufReader::new(tokio::io::stdin()),
+        tokio::io::stdout(),
+    )
+    .await
+}
+
+/// Bucle JSON-RPC por líneas sobre cualquier par lector/escritor (stdin/stdout en producción)
+async fn serve_stdio<R, W>(mut input: R, mut output: W) -> crate::error::Result<()>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+    W: tokio::io::AsyncWrite + Unpin + Send + 'static,
+{
+    use crate::mcp::notifications;
+    use crate::mcp_api::{handle_message, handle_value, RequestContext};
+    use std::sync::Arc;
     use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+    use tokio::sync::{mpsc, Semaphore};
+    use tokio::task::JoinSet;
 
-    tracing::info!("✅ MEMORY_P MCP Stdio listo");
+    let mut buf = Vec::new();
+
+    // Único escritor de la salida: respuestas, progreso y notificaciones, una línea por mensaje.
+    // Respuestas y progreso comparten canal, así el progreso nunca llega tras su respuesta.
+    let (out_tx, mut out_rx) = mpsc::unbounded_channel::<serde_json::Value>();
+    let mut events = notifications::subscribe();
+    let session = mcp::session::Session::standalone();
+    session.attach(out_tx.downgrade());
+    let log_filter = session.clone();
+    let writer = tokio::spawn(async move {
+        loop {
+            // Primero el bus: los logs emitidos durante una tool salen antes que su respuesta
+            let msg = tokio::select! {
+                biased;
+                Some(msg) = notifications::next(&mut events) => {
+                    if !mcp::logging::should_forward(&msg, log_filter.log_level()) {
+                        continue;
+                    }
+                    msg
+                }
+                msg = out_rx.recv() => match msg {
+                    Some(msg) => msg,
+                    None => break,
+                },
+            };
+            if output
+                .write_all(format!("{}\n", msg).as_bytes())
+                .await
+                .is_err()
+                || output.flush().await.is_err()
+            {
+                break;
+            }
+        }
+    });
 
-    let mut stdin = tokio::io::BufReader::new(tokio::io::stdin());
-    let mut stdout = tokio::io::stdout();
-    let mut line = String::new();
-
-    while stdin.read_line(&mut line).await? > 0 {
-        if let Ok(req) = serde_json::from_str::<JsonRpcRequest>(&line) {
-            let response = mcp_json_rpc_handler(axum::Json(req)).await;
-            let resp_json =
-                serde_json::to_string(&response.0).map_err(crate::error::MemoryPError::Json)?;
-            stdout
-                .write_all(format!("{}\n", resp_json).as_bytes())
-                .await?;
-            stdout.flush().await?;
-        }
-        line.clear();
+    let ctx = RequestContext {
+        notify: Some(out_tx.clone()),
+        session: Some(session),
+        ..Default::default()
+    };
+
+    // Una línea = un mensaje JSON-RPC; bytes no UTF-8 llegan al parser como error -32700.
+    // Cada request corre en su propia tarea y responde cuando termina, en cualquier orden.
+    // El turno se toma antes de crear la tarea: con STDIO_MAX_IN_FLIGHT en curso no se lee
+    // más hasta que termine uno. Notificaciones y respuestas del cliente no ocupan turno.
+    let in_flight = Arc::new(Semaphore::new(STDIO_MAX_IN_FLIGHT));
+    let mut handlers = JoinSet::new();
+    loop {
+        let read = tokio::select! {
+            read = input.read_until(b'\n', &mut buf) => read?,
+            _ = mcp::shutdown::requested() => 0,
+        };
+        if read == 0 {
+            break;
+        }
+        let line = String::from_utf8_lossy(&buf).trim().to_string();
+        buf.clear();
+        if line.is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<serde_json::Value>(&line) {
+            Ok(message) if !mcp_api::is_control(&message) => {
+                let permit = tokio::select! {
+                    permit = in_flight.clone().acquire_owned() => permit,
+                    _ = mcp::shutdown::requested() => break,
+                };
+                let Ok(permit) = permit else {
+                    break;
+                };
+                let (ctx, out_tx) = (ctx.clone(), out_tx.clone());
+                handlers.spawn(async move {
+                    let _permit = permit;
+                    if let Some(reply) = handle_value(message, &ctx).await {
+                        let _ = out_tx.send(reply);
+                    }
+                });
+                None
+            }
+            Ok(message) => handle_value(message, &ctx).await,
+            Err(_) => handle_message(&line, &ctx).await,
+        };
+        if let Some(reply) = reply {
+            let _ = out_tx.send(reply);
+        }
+        // Recoger las tareas terminadas para que el JoinSet no crezca sin límite
+        while handlers.try_join_next().is_some() {}
     }
+
+    // EOF o apagado: esperar los requests en curso y vaciar lo pendiente antes de salir
+    while handlers.join_next().await.is_some() {}
+    drop(ctx);
+    drop(out_tx);
+    let _ = writer.await;
     Ok(())
 }
 
@@ -104,6 +297,129 @@ async fn mcp_stdio_mode() -> crate::error::Result<()> {
 async fn error_404() -> (axum::http::StatusCode, String) {
     (
         axum::http::StatusCode::NOT_FOUND,
-        "Endpoint no encontrado. Usa: /analyze, /edit, /repair, /status".to_string(),
+        "Endpoint no encontrado. Usa: /mcp, /analyze_project, /edit_project, /repair_project, /status, /metrics, /health".to_string(),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use crate::mcp::tools::{self, McpTool, ToolContext};
+    use futures::future::BoxFuture;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream, Lines};
+
+    /// Tool que no termina hasta que la cancelan
+    struct WaitTool;
+
+    impl McpTool for WaitTool {
+        fn name(&self) -> &str {
+            "test_stdio_wait"
+        }
+
+        fn description(&self) -> &str {
+            "espera la cancelación"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({ "type": "object" })
+        }
+
+        fn heavy(&self) -> bool {
+            false
+        }
+
+        fn call(&self, _arguments: Value, ctx: ToolContext) -> BoxFuture<'static, Result<Value>> {
+            Box::pin(async move {
+                while !ctx.monitor.is_cancelled() {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+                Ok(tools::text_result("cancelada".to_string()))
+            })
+        }
+    }
+
+    /// Servidor stdio sobre tuberías en memoria: (entrada del servidor, líneas de su salida)
+    fn spawn_stdio() -> (DuplexStream, Lines<BufReader<DuplexStream>>) {
+        let (client_in, server_in) = tokio::io::duplex(1 << 16);
+        let (server_out, client_out) = tokio::io::duplex(1 << 16);
+        tokio::spawn(serve_stdio(BufReader::new(server_in), server_out));
+        (client_in, BufReader::new(client_out).lines())
+    }
+
+    /// Siguiente respuesta (con `id`); cada línea debe ser un JSON completo
+    async fn next_reply(lines: &mut Lines<BufReader<DuplexStream>>) -> Value {
+        loop {
+            let line = tokio::time::timeout(Duration::from_secs(5), lines.next_line())
+                .await
+                .expect("sin respuesta a tiempo")
+                .unwrap()
+                .expect("la salida se cerró");
+            let message: Value = serde_json::from_str(&line).expect("línea JSON entera");
+            if message.get("id").is_some() {
+                return message;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replies_leave_out_of_order() {
+        let _tool = tools::register_scoped(Arc::new(WaitTool));
+        let (mut input, mut lines) = spawn_stdio();
+        let requests = [
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/call",
+                    "params": { "name": "test_stdio_wait", "arguments": {} } }),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "ping" }),
+        ];
+        for request in requests {
+            input
+                .write_all(format!("{}\n", request).as_bytes())
+                .await
+                .unwrap();
+        }
+
+        // El ping no espera detrás de la tool en curso
+        assert_eq!(next_reply(&mut lines).await["id"], 2);
+
+        let cancel = json!({ "jsonrpc": "2.0", "method": "notifications/cancelled",
+                             "params": { "requestId": 1 } });
+        input
+            .write_all(format!("{}\n", cancel).as_bytes())
+            .await
+            .unwrap();
+        let reply = next_reply(&mut lines).await;
+        assert_eq!(reply["id"], 1);
+        assert!(reply["result"]["content"].is_array());
+    }
+
+    #[tokio::test]
+    async fn test_single_writer_keeps_lines_whole() {
+        let (mut input, mut lines) = spawn_stdio();
+        let batch: String = (0..200)
+            .map(|id| {
+                format!(
+                    "{}\n",
+                    json!({ "jsonrpc": "2.0", "id": id, "method": "ping" })
+                )
+            })
+            .collect();
+        input.write_all(batch.as_bytes()).await.unwrap();
+
+        let mut ids = Vec::new();
+        for _ in 0..200 {
+            ids.push(next_reply(&mut lines).await["id"].as_u64().unwrap());
+        }
+        ids.sort_unstable();
+        assert_eq!(ids, (0..200).collect::<Vec<u64>>());
+
+        // Fin de la entrada: el servidor termina y cierra la salida
+        drop(input);
+        let closed = tokio::time::timeout(Duration::from_secs(5), async {
+            while let Ok(Some(_)) = lines.next_line().await {}
+        });
+        assert!(closed.await.is_ok());
+    }
+}
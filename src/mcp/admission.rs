@@ -0,0 +1,170 @@
+//! admission.rs - Control de admisión delante de `tools/call`
+//! Cada tool arma su propio pool de rayon, así que N llamadas simultáneas son N pools compitiendo
+//! por los mismos núcleos. Aquí corren como mucho `[limits] max_concurrent_jobs` a la vez; el resto
+//! espera turno hasta `queue_timeout_ms` (con `max_queued` esperando, se rechaza al instante).
+//! Además cada cliente (token HTTP, sesión o stdio) tiene `rate_per_minute` llamadas con ráfaga
+//! del mismo tamaño (token bucket). Durante el apagado no se admite nada (ni lo que esperaba en
+//! cola). Todo rechazo es `MemoryPError::Busy` con su `retryAfter`.
+
+use crate::config::CONFIG;
+use crate::error::{MemoryPError, Result};
+use crate::mcp::shutdown;
+use lazy_static::lazy_static;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Buckets sin uso por más de esto se descartan
+const BUCKET_IDLE: Duration = Duration::from_secs(600);
+
+lazy_static! {
+    static ref SLOTS: Arc<Semaphore> =
+        Arc::new(Semaphore::new(CONFIG.limits.max_concurrent_jobs.max(1)));
+    static ref QUEUED: AtomicUsize = AtomicUsize::new(0);
+    static ref BUCKETS: scc::HashMap<String, Bucket> = scc::HashMap::new();
+}
+
+/// Turno de ejecución; se libera al soltarlo
+pub struct Admission {
+    _permit: OwnedSemaphorePermit,
+}
+
+/// Lugar en la cola; se libera al soltarlo (también si el request se abandona esperando)
+struct Queued;
+
+impl Drop for Queued {
+    fn drop(&mut self) {
+        QUEUED.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Pide turno para una tool de `client`: límite de tasa, luego un lugar libre o la cola
+pub async fn admit(client: &str) -> Result<Admission> {
+    check_rate(client)?;
+    acquire().await
+}
+
+/// Un lugar libre o, si no hay, la cola (sin límite de tasa: los jobs lo cobran al encolarse)
+pub async fn acquire() -> Result<Admission> {
+    let limits = &CONFIG.limits;
+    if shutdown::is_shutting_down() {
+        return Err(shutting_down());
+    }
+    if let Ok(permit) = SLOTS.clone().try_acquire_owned() {
+        return Ok(Admission { _permit: permit });
+    }
+    let timeout = Duration::from_millis(limits.queue_timeout_ms);
+    let retry_after_secs = timeout.as_secs().max(1);
+    if QUEUED.fetch_add(1, Ordering::Relaxed) >= limits.max_queued {
+        QUEUED.fetch_sub(1, Ordering::Relaxed);
+        return Err(MemoryPError::Busy {
+            reason: format!("cola llena ({} esperando)", limits.max_queued),
+            retry_after_secs,
+        });
+    }
+    let _queued = Queued;
+    let waited = tokio::select! {
+        waited = tokio::time::timeout(timeout, SLOTS.clone().acquire_owned()) => waited,
+        _ = shutdown::requested() => return Err(shutting_down()),
+    };
+    match waited {
+        Ok(Ok(permit)) => Ok(Admission { _permit: permit }),
+        _ => Err(MemoryPError::Busy {
+            reason: format!(
+                "{} tools en curso; sin turno tras {}ms en cola",
+                limits.max_concurrent_jobs, limits.queue_timeout_ms
+            ),
+            retry_after_secs,
+        }),
+    }
+}
+
+fn shutting_down() -> MemoryPError {
+    MemoryPError::Busy {
+        reason: "servidor apagándose".to_string(),
+        retry_after_secs: CONFIG.shutdown.drain_timeout_secs.max(1),
+    }
+}
+
+/// Ocupación para `/status`
+pub fn snapshot() -> Value {
+    let max = CONFIG.limits.max_concurrent_jobs.max(1);
+    json!({
+        "running": max - SLOTS.available_permits(),
+        "queued": QUEUED.load(Ordering::Relaxed),
+        "max_concurrent_jobs": max,
+    })
+}
+
+/// Cobra una llamada a `client` según `[limits] rate_per_minute`
+pub fn check_rate(client: &str) -> Result<()> {
+    let rate_per_minute = CONFIG.limits.rate_per_minute;
+    if rate_per_minute == 0 {
+        return Ok(());
+    }
+    let now = Instant::now();
+    if BUCKETS.len() > 1024 {
+        BUCKETS.retain(|_, b| now.duration_since(b.updated) < BUCKET_IDLE);
+    }
+    let mut bucket = BUCKETS
+        .entry(client.to_string())
+        .or_insert_with(|| Bucket::full(rate_per_minute, now));
+    bucket
+        .get_mut()
+        .take(rate_per_minute, now)
+        .map_err(|wait| MemoryPError::Busy {
+            reason: format!(
+                "límite de {} llamadas por minuto para {}",
+                rate_per_minute, client
+            ),
+            retry_after_secs: wait.as_secs_f64().ceil().max(1.0) as u64,
+        })
+}
+
+/// Token bucket: capacidad `rate` llamadas, recarga `rate` por minuto
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn full(rate: u32, now: Instant) -> Self {
+        Self {
+            tokens: rate as f64,
+            updated: now,
+        }
+    }
+
+    /// Consume una llamada, o dice cuánto falta para la próxima
+    fn take(&mut self, rate: u32, now: Instant) -> std::result::Result<(), Duration> {
+        let per_sec = rate as f64 / 60.0;
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_sec).min(rate as f64);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / per_sec))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_bursts_then_refills() {
+        let start = Instant::now();
+        let mut bucket = Bucket::full(2, start);
+        assert!(bucket.take(2, start).is_ok());
+        assert!(bucket.take(2, start).is_ok());
+        // 2 por minuto: la próxima llega en 30s
+        let wait = bucket.take(2, start).unwrap_err();
+        assert_eq!(wait.as_secs_f64().round(), 30.0);
+        assert!(bucket.take(2, start + Duration::from_secs(31)).is_ok());
+    }
+}
@@ -0,0 +1,220 @@
+//! auth.rs - Autenticación Bearer del transporte HTTP
+//! Tokens estáticos de `[[auth.tokens]]` (valor en `token` o en la variable `env`) más
+//! `MEMORY_P_TOKEN`, que concede todos los permisos. Sin ningún token el servidor queda abierto,
+//! como antes. `/health` nunca pide token; el resto de rutas exige uno válido (401), y las tools
+//! y endpoints heredados exigen además el permiso correspondiente (403). stdio no pasa por aquí.
+
+use crate::config::CONFIG;
+use crate::error::{MemoryPError, Result};
+use crate::mcp::models::JsonRpcResponse;
+use axum::{
+    extract::Request,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Token con todos los permisos, para despliegues de un solo usuario
+pub const TOKEN_ENV: &str = "MEMORY_P_TOKEN";
+
+/// Rutas que no piden token (sondeos de vida)
+const PUBLIC_PATHS: &[&str] = &["/health"];
+
+/// Permiso que concede un token
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Análisis y lecturas
+    Read,
+    /// Escribir archivos: edit, repair, workflow
+    Edit,
+    /// Borrar archivos
+    Delete,
+    /// Mega simulaciones
+    Simulate,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 4] = [Scope::Read, Scope::Edit, Scope::Delete, Scope::Simulate];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Edit => "edit",
+            Scope::Delete => "delete",
+            Scope::Simulate => "simulate",
+        }
+    }
+}
+
+/// Identidad y permisos del token que autenticó un request HTTP
+#[derive(Debug, Clone)]
+pub struct Grant {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+}
+
+impl Grant {
+    pub fn require(&self, scope: Scope) -> Result<()> {
+        if self.scopes.contains(&scope) {
+            Ok(())
+        } else {
+            Err(MemoryPError::Forbidden(format!(
+                "el token '{}' no tiene el permiso '{}'",
+                self.name,
+                scope.as_str()
+            )))
+        }
+    }
+}
+
+lazy_static! {
+    /// (secreto, permisos) de cada token configurado
+    static ref TOKENS: Vec<(String, Grant)> = load_tokens();
+}
+
+fn load_tokens() -> Vec<(String, Grant)> {
+    let mut tokens = Vec::new();
+    for entry in &CONFIG.auth.tokens {
+        let secret = entry
+            .token
+            .clone()
+            .or_else(|| entry.env.as_ref().and_then(|var| std::env::var(var).ok()));
+        match secret.filter(|s| !s.is_empty()) {
+            Some(secret) => tokens.push((
+                secret,
+                Grant {
+                    name: entry.name.clone(),
+                    scopes: entry.scopes.clone(),
+                },
+            )),
+            None => tracing::warn!("⚠️ Token '{}' sin valor (token/env), se ignora", entry.name),
+        }
+    }
+    if let Some(secret) = std::env::var(TOKEN_ENV).ok().filter(|s| !s.is_empty()) {
+        tokens.push((
+            secret,
+            Grant {
+                name: TOKEN_ENV.to_string(),
+                scopes: Scope::ALL.to_vec(),
+            },
+        ));
+    }
+    tokens
+}
+
+/// ¿Hay tokens configurados? Si no, HTTP no exige autenticación
+pub fn enabled() -> bool {
+    !TOKENS.is_empty()
+}
+
+/// Carga los tokens al arrancar y avisa si el servidor HTTP queda abierto
+pub fn init() {
+    if enabled() {
+        tracing::info!("🔐 Autenticación Bearer activa ({} tokens)", TOKENS.len());
+    } else {
+        tracing::warn!(
+            "🔓 Sin tokens en [auth] ni {}: HTTP sin autenticación",
+            TOKEN_ENV
+        );
+    }
+}
+
+fn authenticate(headers: &HeaderMap) -> Option<Grant> {
+    let presented = bearer(headers)?;
+    TOKENS
+        .iter()
+        .find(|(secret, _)| constant_time_eq(secret.as_bytes(), presented.as_bytes()))
+        .map(|(_, grant)| grant.clone())
+}
+
+/// Valor de `Authorization: Bearer <token>`
+fn bearer(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    scheme
+        .eq_ignore_ascii_case("bearer")
+        .then(|| token.trim())
+        .filter(|t| !t.is_empty())
+}
+
+/// Comparación que no corta en el primer byte distinto
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Permiso que exigen las rutas fuera de `/mcp` (en `/mcp` se decide por tool)
+fn route_scope(path: &str) -> Option<Scope> {
+    match path {
+        "/status" | "/metrics" | "/analyze_project" => Some(Scope::Read),
+        "/create_project" | "/edit_project" | "/repair_project" | "/ultra" => Some(Scope::Edit),
+        // Crear un job exige además el permiso de su tool; leerlo o cancelarlo, ser su dueño
+        "/jobs" => Some(Scope::Read),
+        p if p.starts_with("/jobs/") => Some(Scope::Read),
+        _ => None,
+    }
+}
+
+/// Middleware de `mcp_api::routes`: valida el token y deja el `Grant` en las extensiones
+pub async fn require_token(mut req: Request, next: Next) -> Response {
+    let path = req.uri().path();
+    if !enabled() || PUBLIC_PATHS.contains(&path) {
+        return next.run(req).await;
+    }
+    let Some(grant) = authenticate(req.headers()) else {
+        let err = MemoryPError::Unauthorized("falta un token Bearer válido".to_string());
+        let mut response = reject(StatusCode::UNAUTHORIZED, &err);
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        return response;
+    };
+    if let Some(Err(err)) = route_scope(path).map(|scope| grant.require(scope)) {
+        tracing::warn!("🚫 {} rechazado: {}", path, err);
+        return reject(StatusCode::FORBIDDEN, &err);
+    }
+    req.extensions_mut().insert(grant);
+    next.run(req).await
+}
+
+/// Rechazo con cuerpo de error JSON-RPC (`id: null`), el mismo formato que el resto de `/mcp`
+pub fn reject(status: StatusCode, err: &MemoryPError) -> Response {
+    (status, Json(JsonRpcResponse::failure(Value::Null, err))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bearer_and_scopes() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer(&headers), None);
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer abc"),
+        );
+        assert_eq!(bearer(&headers), Some("abc"));
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Basic abc"));
+        assert_eq!(bearer(&headers), None);
+
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"abcd"));
+
+        let grant = Grant {
+            name: "ci".to_string(),
+            scopes: vec![Scope::Read],
+        };
+        assert!(grant.require(Scope::Read).is_ok());
+        let err = grant.require(Scope::Delete).unwrap_err();
+        assert_eq!(err.rpc_code(), crate::error::FORBIDDEN);
+        assert_eq!(route_scope("/edit_project"), Some(Scope::Edit));
+        assert_eq!(route_scope("/mcp"), None);
+        assert_eq!(route_scope("/jobs/abc/events"), Some(Scope::Read));
+    }
+}
@@ -0,0 +1,120 @@
+//! cancellation.rs - `notifications/cancelled` para tools en curso
+//! Cada `tools/call` en ejecución se registra con su `JobMonitor`, el ámbito del cliente (su
+//! sesión, o su token en HTTP sin sesión) y el `id` del request. HTTP sin sesión ni token no
+//! tiene ámbito: sus requests solo se cancelan en el apagado.
+
+use crate::parallel_engine::JobMonitor;
+use lazy_static::lazy_static;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Request en curso: quién puede cancelarlo y cómo
+struct Entry {
+    /// `None` = nadie puede cancelarlo por id (HTTP sin sesión ni token)
+    scope: Option<String>,
+    /// El id se serializa tal cual: `1` y `"1"` son requests distintos
+    request_id: String,
+    monitor: Arc<JobMonitor>,
+}
+
+lazy_static! {
+    /// Indexado por un token único por llamada: dos requests con el mismo id no se pisan
+    static ref IN_FLIGHT: scc::HashMap<u64, Entry> = scc::HashMap::new();
+}
+
+static NEXT_TOKEN: AtomicU64 = AtomicU64::new(1);
+
+/// Registro de un request en curso; se retira al soltarlo
+pub struct InFlight {
+    token: u64,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        IN_FLIGHT.remove(&self.token);
+    }
+}
+
+pub fn register(scope: Option<&str>, request_id: &Value, monitor: Arc<JobMonitor>) -> InFlight {
+    let token = NEXT_TOKEN.fetch_add(1, Ordering::Relaxed);
+    let _ = IN_FLIGHT.insert(
+        token,
+        Entry {
+            scope: scope.map(str::to_string),
+            request_id: request_id.to_string(),
+            monitor,
+        },
+    );
+    InFlight { token }
+}
+
+/// Marca el request como cancelado; `false` si ya terminó o nunca existió en ese ámbito
+pub fn cancel(scope: &str, request_id: &Value) -> bool {
+    let request_id = request_id.to_string();
+    let mut cancelled = false;
+    IN_FLIGHT.scan(|_, entry| {
+        if entry.scope.as_deref() == Some(scope) && entry.request_id == request_id {
+            entry.monitor.cancel();
+            cancelled = true;
+        }
+    });
+    cancelled
+}
+
+/// Cancela todas las tools en curso (apagado); devuelve cuántas
+pub fn cancel_all() -> usize {
+    let mut cancelled = 0;
+    IN_FLIGHT.scan(|_, entry| {
+        entry.monitor.cancel();
+        cancelled += 1;
+    });
+    cancelled
+}
+
+/// Tools en ejecución en este momento
+pub fn active() -> usize {
+    IN_FLIGHT.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cancel_only_while_in_flight() {
+        let monitor = Arc::new(JobMonitor::default());
+        let guard = register(Some("s1"), &json!(7), monitor.clone());
+        assert!(!cancel("s2", &json!(7)));
+        assert!(!cancel("s1", &json!("7")));
+        assert!(cancel("s1", &json!(7)));
+        assert!(monitor.is_cancelled());
+
+        drop(guard);
+        assert!(!cancel("s1", &json!(7)));
+    }
+
+    #[test]
+    fn test_same_id_calls_do_not_collide() {
+        let first = Arc::new(JobMonitor::default());
+        let second = Arc::new(JobMonitor::default());
+        let first_guard = register(Some("s3"), &json!(1), first.clone());
+        let second_guard = register(Some("s3"), &json!(1), second.clone());
+
+        // Soltar el primero no retira el registro del segundo
+        drop(first_guard);
+        assert!(cancel("s3", &json!(1)));
+        assert!(second.is_cancelled());
+        assert!(!first.is_cancelled());
+        drop(second_guard);
+    }
+
+    #[test]
+    fn test_unscoped_calls_cannot_be_cancelled_by_id() {
+        let monitor = Arc::new(JobMonitor::default());
+        let _guard = register(None, &json!("anon-1"), monitor.clone());
+        assert!(!cancel("", &json!("anon-1")));
+        assert!(!monitor.is_cancelled());
+    }
+}
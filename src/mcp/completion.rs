@@ -0,0 +1,234 @@
+//! completion.rs - `completion/complete` para argumentos de tools y prompts
+//! Además de `ref/prompt` y `ref/resource` (MCP), acepta `{"type": "ref/tool", "name": ...}`
+//! para completar argumentos de tools:
+//! - valores `enum` del `inputSchema` (`mode`, `phase`...)
+//! - `path` / `paths`: rutas dentro de las raíces de la sesión, respetando `.gitignore`
+//! - `extension`: extensiones presentes bajo `context.arguments.path` (si está en las raíces)
+//! - `name` de `workflow`: workflows guardados en `PAYLOAD_BANK/WORKFLOWS`
+
+use crate::error::{MemoryPError, Result};
+use crate::mcp::{prompts, roots, tools};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Máximo de valores por respuesta (límite de la spec)
+const MAX_VALUES: usize = 100;
+
+/// Archivos a recorrer como mucho para sugerir extensiones
+const EXTENSION_SCAN_LIMIT: usize = 5000;
+
+/// `roots`: raíces vigentes del cliente; no se sugiere nada fuera de ellas
+pub fn complete(params: &Value, roots: &[PathBuf]) -> Result<Value> {
+    let reference = params
+        .get("ref")
+        .ok_or_else(|| MemoryPError::InvalidParams("Falta el parámetro 'ref'".to_string()))?;
+    let argument = params.get("argument").unwrap_or(&Value::Null);
+    let arg_name = argument
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| MemoryPError::InvalidParams("Falta 'argument.name'".to_string()))?;
+    let prefix = argument.get("value").and_then(|v| v.as_str()).unwrap_or("");
+    let context = params.pointer("/context/arguments").unwrap_or(&Value::Null);
+
+    let candidates = match reference.get("type").and_then(|v| v.as_str()) {
+        Some("ref/tool") => {
+            let name = ref_name(reference)?;
+            let tool = tools::get(name).ok_or_else(|| {
+                MemoryPError::InvalidParams(format!("Tool desconocida: {}", name))
+            })?;
+            tool_candidates(name, &tool.input_schema(), arg_name, prefix, context, roots)
+        }
+        Some("ref/prompt") => {
+            let name = ref_name(reference)?;
+            if !prompts::list().iter().any(|p| p.name == name) {
+                return Err(MemoryPError::InvalidParams(format!(
+                    "Prompt desconocido: {}",
+                    name
+                )));
+            }
+            // Los argumentos de prompts son texto libre
+            Vec::new()
+        }
+        Some("ref/resource") => Vec::new(),
+        other => {
+            return Err(MemoryPError::InvalidParams(format!(
+                "Tipo de 'ref' no soportado: {}",
+                other.unwrap_or("(ninguno)")
+            )))
+        }
+    };
+
+    let total = candidates.len();
+    let values: Vec<String> = candidates.into_iter().take(MAX_VALUES).collect();
+    Ok(json!({
+        "completion": { "values": values, "total": total, "hasMore": total > MAX_VALUES }
+    }))
+}
+
+fn ref_name(reference: &Value) -> Result<&str> {
+    reference
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| MemoryPError::InvalidParams("Falta 'ref.name'".to_string()))
+}
+
+fn tool_candidates(
+    tool: &str,
+    schema: &Value,
+    arg_name: &str,
+    prefix: &str,
+    context: &Value,
+    roots: &[PathBuf],
+) -> Vec<String> {
+    if let Some(values) = schema
+        .pointer(&format!("/properties/{}/enum", arg_name))
+        .and_then(|e| e.as_array())
+    {
+        return values
+            .iter()
+            .map(|v| {
+                v.as_str()
+                    .map(String::from)
+                    .unwrap_or_else(|| v.to_string())
+            })
+            .filter(|v| v.starts_with(prefix))
+            .collect();
+    }
+    match (tool, arg_name) {
+        (_, "path" | "paths") => paths(prefix, roots),
+        (_, "extension") => {
+            let root = context.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+            match roots::confine(root, roots) {
+                Ok(root) => extensions(&root, prefix),
+                Err(_) => Vec::new(),
+            }
+        }
+        ("workflow", "name") => tools::saved_workflows()
+            .into_iter()
+            .filter(|name| name.starts_with(prefix))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Entradas del directorio de `prefix` que empiezan con su último segmento;
+/// los directorios terminan en `/` para poder seguir completando.
+/// Solo se ofrece lo que está dentro de una raíz o los directorios que llevan a una.
+fn paths(prefix: &str, roots: &[PathBuf]) -> Vec<String> {
+    let (dir, partial) = match prefix.rfind(['/', '\\']) {
+        Some(i) => (&prefix[..=i], &prefix[i + 1..]),
+        None => ("", prefix),
+    };
+    let walk_root = if dir.is_empty() { "." } else { dir };
+    if !Path::new(walk_root).is_dir() {
+        return Vec::new();
+    }
+
+    // Mismo filtro que CodeAnalyzer::scan_files: .gitignore y ocultos (salvo que se pidan con '.')
+    let mut entries: Vec<String> = ignore::WalkBuilder::new(walk_root)
+        .max_depth(Some(1))
+        .hidden(!partial.starts_with('.'))
+        .git_ignore(true)
+        .build()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.depth() == 1)
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            if !name.starts_with(partial) || !reachable(e.path(), roots) {
+                return None;
+            }
+            let is_dir = e.file_type().is_some_and(|ft| ft.is_dir());
+            Some(format!("{}{}{}", dir, name, if is_dir { "/" } else { "" }))
+        })
+        .collect();
+    entries.sort();
+    entries
+}
+
+/// Dentro de alguna raíz (`roots::confine`) o ancestro de una, para poder navegar hasta ella
+fn reachable(path: &Path, roots: &[PathBuf]) -> bool {
+    if roots::confine(&path.to_string_lossy(), roots).is_ok() {
+        return true;
+    }
+    std::fs::canonicalize(path).is_ok_and(|dir| roots.iter().any(|root| root.starts_with(&dir)))
+}
+
+/// Extensiones bajo `root`, de la más a la menos frecuente
+fn extensions(root: &Path, prefix: &str) -> Vec<String> {
+    if !root.is_dir() {
+        return Vec::new();
+    }
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    ignore::WalkBuilder::new(root)
+        .git_ignore(true)
+        .build()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_some_and(|ft| ft.is_file()))
+        .take(EXTENSION_SCAN_LIMIT)
+        .filter_map(|e| {
+            e.path()
+                .extension()
+                .map(|ext| ext.to_string_lossy().into_owned())
+        })
+        .filter(|ext| ext.starts_with(prefix))
+        .for_each(|ext| *counts.entry(ext).or_default() += 1);
+
+    let mut found: Vec<(String, usize)> = counts.into_iter().collect();
+    found.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    found.into_iter().map(|(ext, _)| ext).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complete_enum_and_paths() {
+        tools::install_builtin();
+        let roots = roots::fallback();
+        let result = complete(
+            &json!({
+                "ref": { "type": "ref/tool", "name": "analyze" },
+                "argument": { "name": "mode", "value": "q" }
+            }),
+            &roots,
+        )
+        .unwrap();
+        assert_eq!(result["completion"]["values"], json!(["quick"]));
+
+        let result = complete(
+            &json!({
+                "ref": { "type": "ref/tool", "name": "repair" },
+                "argument": { "name": "path", "value": "sr" }
+            }),
+            &roots,
+        )
+        .unwrap();
+        assert_eq!(result["completion"]["values"], json!(["src/"]));
+
+        let result = complete(
+            &json!({
+                "ref": { "type": "ref/tool", "name": "analyze" },
+                "argument": { "name": "extension", "value": "r" },
+                "context": { "arguments": { "path": "src" } }
+            }),
+            &roots,
+        )
+        .unwrap();
+        assert_eq!(result["completion"]["values"][0], "rs");
+    }
+
+    #[test]
+    fn test_paths_stay_inside_roots() {
+        let src = std::fs::canonicalize("src").unwrap();
+        let roots = vec![src.join("mcp")];
+
+        // Fuera de la raíz solo aparecen los directorios que llevan a ella
+        assert_eq!(paths("src/", &roots), vec!["src/mcp/"]);
+        assert!(paths("", &roots).contains(&"src/".to_string()));
+        assert!(!paths("", &roots).contains(&"Cargo.toml".to_string()));
+        assert!(paths("src/mcp/ses", &roots).contains(&"src/mcp/session.rs".to_string()));
+        assert!(paths("/et", &roots).is_empty());
+    }
+}
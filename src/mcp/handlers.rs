@@ -1,16 +1,27 @@
 // [nuclear_god_mode] PROCESSED AT MAX SPEED
 use crate::analyzer::CodeAnalyzer;
+use crate::error::MemoryPError;
+use crate::mcp::auth::Grant;
 use crate::mcp::models::*;
+use crate::mcp::session::{self, Session, SessionEvent};
+use crate::mcp::{admission, cancellation, jobs, logging, metrics, notifications, resources, roots, shutdown, status};
+use crate::mcp_api::RequestContext;
+use crate::parallel_engine::{JobMonitor, ParallelConfig, ProcessingStatus, UltraParallelEngine};
 use crate::workspace;
 use axum::{
-    response::sse::{Event, Sse},
-    response::IntoResponse,
-    Json,
+    extract::Path,
+    http::{header, HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+    response::{IntoResponse, Response},
+    Extension, Json,
 };
 use futures::stream::{self, Stream, StreamExt};
 use serde_json::{json, Value};
 use std::convert::Infallible;
+use std::path::PathBuf;
 use std::process::Command;
+use std::sync::Arc;
+use tokio::sync::broadcast;
 
 pub async fn mcp_descriptor_handler() -> impl IntoResponse {
     let descriptor = McpDescriptor {
@@ -21,16 +32,217 @@ pub async fn mcp_descriptor_handler() -> impl IntoResponse {
     Json(descriptor)
 }
 
+/// GET /status: estado completo para supervisores y dashboards
+pub async fn status_handler() -> Json<Value> {
+    Json(status::snapshot())
+}
+
+/// GET /metrics: contadores e histogramas en formato de texto de Prometheus
+pub async fn metrics_handler() -> impl IntoResponse {
+    (
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        metrics::render(),
+    )
+}
+
+/// GET /health: sondeo liviano de vida
+pub async fn health_handler() -> Json<Value> {
+    Json(json!({ "status": "ok", "uptime_secs": status::uptime_secs() }))
+}
+
+/// Canal SSE de notificaciones globales del servidor (list_changed, logs...)
 pub async fn mcp_sse_handler() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let stream = stream::repeat_with(|| Event::default().data("connected")).map(Ok);
+    let stream = stream::unfold(notifications::subscribe(), |mut rx| async move {
+        let msg = loop {
+            let msg = notifications::next(&mut rx).await?;
+            if logging::should_forward(&msg, logging::sessionless_level()) {
+                break msg;
+            }
+        };
+        let event = Event::default().event("message").data(msg.to_string());
+        Some((Ok(event), rx))
+    });
+
+    Sse::new(stream.take_until(shutdown::requested())).keep_alive(KeepAlive::default())
+}
+
+/// GET /mcp: stream SSE de mensajes iniciados por el servidor para una sesión.
+/// Con `Last-Event-ID` reenvía primero los eventos perdidos de ese stream.
+pub async fn mcp_stream_handler(headers: HeaderMap) -> Response {
+    if !accepts(&headers, "text/event-stream") {
+        return StatusCode::NOT_ACCEPTABLE.into_response();
+    }
+    let session = match session_from_headers(&headers) {
+        Ok(s) => s,
+        Err(status) => return status.into_response(),
+    };
+
+    let replay = headers
+        .get(session::LAST_EVENT_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(|last| session.replay_after(last))
+        .unwrap_or_default();
+
+    let live = stream::unfold(
+        (session.subscribe(), session.closed()),
+        |(mut rx, mut closed)| async move {
+            loop {
+                tokio::select! {
+                    event = rx.recv() => match event {
+                        Ok(event) => return Some((event, (rx, closed))),
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    },
+                    _ = closed.changed() => return None,
+                }
+            }
+        },
+    );
+    let events = stream::iter(replay)
+        .chain(live)
+        .take_until(shutdown::requested())
+        .map(|event| Ok::<_, Infallible>(sse_event(&event)));
+
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// DELETE /mcp: el cliente termina su sesión
+pub async fn mcp_delete_session_handler(headers: HeaderMap) -> StatusCode {
+    match session_from_headers(&headers) {
+        Ok(s) if session::remove(&s.id) => StatusCode::NO_CONTENT,
+        Ok(_) => StatusCode::NOT_FOUND,
+        Err(status) => status,
+    }
+}
+
+/// POST /jobs: `{"tool": ..., "arguments": {...}}` → 202 con el job encolado
+pub async fn create_job_handler(
+    grant: Option<Extension<Grant>>,
+    Json(body): Json<Value>,
+) -> Response {
+    let grant = grant.map(|Extension(grant)| grant);
+    let client = RequestContext {
+        grant: grant.clone(),
+        ..Default::default()
+    }
+    .client();
+    let Some(tool) = body.get("tool").and_then(|v| v.as_str()) else {
+        return job_error(&MemoryPError::InvalidParams(
+            "Falta 'tool' (nombre de la tool)".to_string(),
+        ));
+    };
+    let arguments = body.get("arguments").cloned().unwrap_or(Value::Null);
+    match jobs::start(tool, arguments, grant.as_ref(), &client) {
+        Ok(job) => (StatusCode::ACCEPTED, Json(job.view(false))).into_response(),
+        Err(err) => job_error(&err),
+    }
+}
+
+/// GET /jobs/{id}: estado, progreso y, si terminó, resultado o error
+pub async fn job_handler(
+    grant: Option<Extension<Grant>>,
+    Path(id): Path<String>,
+) -> Response {
+    match jobs::get(&id, grant.as_deref()) {
+        Some(job) => Json(job.view(true)).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// GET /jobs/{id}/events: `progress` mientras corre y un `done` final con el resultado
+pub async fn job_events_handler(
+    grant: Option<Extension<Grant>>,
+    Path(id): Path<String>,
+) -> Response {
+    let Some(job) = jobs::get(&id, grant.as_deref()) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let events = jobs::events(job).map(|(kind, data)| {
+        Ok::<_, Infallible>(Event::default().event(kind).data(data.to_string()))
+    });
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// DELETE /jobs/{id}: cancela uno en curso (202) o descarta uno terminado (204)
+pub async fn delete_job_handler(
+    grant: Option<Extension<Grant>>,
+    Path(id): Path<String>,
+) -> Response {
+    let Some(job) = jobs::get(&id, grant.as_deref()) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if job.state().is_finished() {
+        jobs::remove(&id);
+        return StatusCode::NO_CONTENT.into_response();
+    }
+    job.cancel();
+    (StatusCode::ACCEPTED, Json(job.view(false))).into_response()
+}
+
+/// `{"error": {code, message, data}}` con el estado HTTP que corresponde al error
+fn job_error(err: &MemoryPError) -> Response {
+    let status = match err {
+        MemoryPError::Forbidden(_) => StatusCode::FORBIDDEN,
+        MemoryPError::Busy { .. } => StatusCode::TOO_MANY_REQUESTS,
+        _ => StatusCode::BAD_REQUEST,
+    };
+    let mut response = (status, Json(json!({ "error": JsonRpcError::from(err) }))).into_response();
+    if let MemoryPError::Busy {
+        retry_after_secs, ..
+    } = err
+    {
+        if let Ok(value) = header::HeaderValue::from_str(&retry_after_secs.to_string()) {
+            response.headers_mut().insert(header::RETRY_AFTER, value);
+        }
+    }
+    response
+}
 
-    Sse::new(stream)
+/// Sesión indicada en `Mcp-Session-Id`: 400 si falta, 404 si no existe
+fn session_from_headers(headers: &HeaderMap) -> Result<Arc<Session>, StatusCode> {
+    let id = headers
+        .get(session::SESSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    session::get(id).ok_or(StatusCode::NOT_FOUND)
+}
+
+pub fn accepts(headers: &HeaderMap, mime: &str) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(|v| v.contains(mime) || v.contains("*/*"))
+}
+
+pub fn sse_event(event: &SessionEvent) -> Event {
+    Event::default()
+        .id(event.id.to_string())
+        .event("message")
+        .data(event.message.to_string())
 }
 
 pub async fn create_project_handler(
     Json(payload): Json<CreateProjectRequest>,
 ) -> Json<CreateProjectResponse> {
     let base_path = std::path::Path::new(&payload.path);
+    if let Err(e) = roots::confine(
+        &base_path.join(&payload.name).to_string_lossy(),
+        &roots::fallback(),
+    ) {
+        return Json(CreateProjectResponse {
+            status: e.to_string(),
+            created_files: vec![],
+        });
+    }
     if let Err(e) = std::fs::create_dir_all(base_path) {
         return Json(CreateProjectResponse {
             status: format!("Error al crear directorio base: {}", e),
@@ -189,106 +401,155 @@ if __name__ == "__main__":
     })
 }
 
-pub async fn analyze_project_handler(Json(payload): Json<ProjectRequest>) -> Json<ProjectResponse> {
-    let ext = payload.extension.as_deref().unwrap_or("rs");
-    let config = crate::parallel_engine::ParallelConfig::default();
+pub async fn analyze_project_handler(
+    grant: Option<Extension<Grant>>,
+    Json(payload): Json<ProjectRequest>,
+) -> Response {
+    run_legacy(grant, move |config| {
+        let ext = payload.extension.as_deref().unwrap_or("rs");
+        match scan_confined(&payload.path, ext) {
+            Ok(paths) => match crate::parallel_engine::ultra_analyze(&paths, config) {
+                Ok((results, stats)) => {
+                    resources::record_analysis(&payload.path, "deep", ext, &results, &stats);
+                    let formatted: Vec<Value> = results
+                        .into_iter()
+                        .map(|r| json!(format!("{}: [{}]", r.path, r.findings.join(", "))))
+                        .collect();
+                    ProjectResponse {
+                        status: "Done".into(),
+                        results: formatted,
+                    }
+                }
+                Err(e) => legacy_error("Error de procesamiento", e),
+            },
+            Err(e) => legacy_error("Error de escaneo", e),
+        }
+    })
+    .await
+}
 
-    match CodeAnalyzer::scan_files(&payload.path, ext, true, false) {
-        Ok(paths) => match crate::parallel_engine::ultra_analyze(&paths, config) {
-            Ok((results, _stats)) => {
-                let formatted: Vec<Value> = results
-                    .into_iter()
-                    .map(|r| json!(format!("{}: [{}]", r.path, r.findings.join(", "))))
+pub async fn edit_project_handler(
+    grant: Option<Extension<Grant>>,
+    Json(payload): Json<ProjectRequest>,
+) -> Response {
+    run_legacy(grant, move |config| {
+        let ext = payload.extension.as_deref().unwrap_or("rs");
+        match scan_confined(&payload.path, ext) {
+            Ok(paths) => {
+                // Convertimos paths a FileChanges genéricos para el motor de edición masiva
+                let changes: Vec<FileChange> = paths
+                    .iter()
+                    .map(|p| FileChange {
+                        path: p.to_string_lossy().to_string(),
+                        operations: vec![EditOp::Replace {
+                            target: "\t".to_string(),
+                            replacement: "    ".to_string(),
+                        }], // Ejemplo de normalización base
+                    })
                     .collect();
-                Json(ProjectResponse {
-                    status: "Done".into(),
-                    results: formatted,
-                })
+
+                match crate::parallel_engine::ultra_edit(&changes, config, false) {
+                    Ok((results, _stats)) => {
+                        let formatted: Vec<Value> = results
+                            .into_iter()
+                            .map(|r| json!(format!("{}: {:?}", r.path, r.status)))
+                            .collect();
+                        ProjectResponse {
+                            status: "Done".into(),
+                            results: formatted,
+                        }
+                    }
+                    Err(e) => legacy_error("Error de procesamiento", e),
+                }
             }
-            Err(e) => Json(ProjectResponse {
-                status: "Error".into(),
-                results: vec![json!(format!("Error de procesamiento: {}", e))],
-            }),
-        },
-        Err(e) => Json(ProjectResponse {
-            status: "Error".into(),
-            results: vec![json!(format!("Error de escaneo: {}", e))],
-        }),
-    }
+            Err(e) => legacy_error("Error de escaneo", e),
+        }
+    })
+    .await
 }
 
-pub async fn edit_project_handler(Json(payload): Json<ProjectRequest>) -> Json<ProjectResponse> {
-    let ext = payload.extension.as_deref().unwrap_or("rs");
-    let config = crate::parallel_engine::ParallelConfig::default();
-
-    match CodeAnalyzer::scan_files(&payload.path, ext, true, false) {
-        Ok(paths) => {
-            // Convertimos paths a FileChanges genéricos para el motor de edición masiva
-            let changes: Vec<FileChange> = paths
-                .iter()
-                .map(|p| FileChange {
-                    path: p.to_string_lossy().to_string(),
-                    operations: vec![EditOp::Replace {
-                        target: "\t".to_string(),
-                        replacement: "    ".to_string(),
-                    }], // Ejemplo de normalización base
-                })
-                .collect();
-
-            match crate::parallel_engine::ultra_edit(&changes, config, false) {
+pub async fn repair_project_handler(
+    grant: Option<Extension<Grant>>,
+    Json(payload): Json<ProjectRequest>,
+) -> Response {
+    run_legacy(grant, move |config| {
+        let ext = payload.extension.as_deref().unwrap_or("rs");
+        match scan_confined(&payload.path, ext) {
+            // Mismo motor que las tools para que el monitor vea (y corte) cada archivo
+            Ok(paths) => match UltraParallelEngine::new(config).process_files(&paths, |path, _| {
+                Ok((workspace::repair_file(path)?, ProcessingStatus::Success))
+            }) {
                 Ok((results, _stats)) => {
                     let formatted: Vec<Value> = results
                         .into_iter()
-                        .map(|r| json!(format!("{}: {:?}", r.path, r.status)))
+                        .map(|r| match r.status {
+                            ProcessingStatus::Error => {
+                                json!(format!("ERROR: {}: {}", r.path, r.findings.join(", ")))
+                            }
+                            _ => json!(r.findings.join(", ")),
+                        })
                         .collect();
-                    Json(ProjectResponse {
+                    ProjectResponse {
                         status: "Done".into(),
                         results: formatted,
-                    })
+                    }
                 }
-                Err(e) => Json(ProjectResponse {
-                    status: "Error".into(),
-                    results: vec![json!(format!("Error de procesamiento: {}", e))],
-                }),
-            }
+                Err(e) => legacy_error("Error de procesamiento", e),
+            },
+            Err(e) => legacy_error("Error de escaneo", e),
         }
-        Err(e) => Json(ProjectResponse {
-            status: "Error".into(),
-            results: vec![json!(format!("Error de escaneo: {}", e))],
-        }),
+    })
+    .await
+}
+
+/// Las rutas heredadas siguen el camino de `tools/call`: admisión y límite de tasa del cliente,
+/// monitor registrado en `cancellation` (el apagado las espera y las corta) y el trabajo fuera
+/// del runtime async
+async fn run_legacy<F>(grant: Option<Extension<Grant>>, work: F) -> Response
+where
+    F: FnOnce(ParallelConfig) -> ProjectResponse + Send + 'static,
+{
+    let client = RequestContext {
+        grant: grant.map(|Extension(grant)| grant),
+        ..Default::default()
+    }
+    .client();
+    let _admission = match admission::admit(&client).await {
+        Ok(admission) => admission,
+        Err(err) => return job_error(&err),
+    };
+    let monitor = Arc::new(JobMonitor::default());
+    let _in_flight = cancellation::register(None, &Value::Null, monitor.clone());
+    let config = ParallelConfig {
+        monitor: Some(monitor),
+        ..Default::default()
+    };
+    match tokio::task::spawn_blocking(move || work(config)).await {
+        Ok(response) => Json(response).into_response(),
+        Err(e) => Json(legacy_error("Error de procesamiento", e)).into_response(),
     }
 }
 
-pub async fn repair_project_handler(Json(payload): Json<ProjectRequest>) -> Json<ProjectResponse> {
-    let ext = payload.extension.as_deref().unwrap_or("rs");
-    match CodeAnalyzer::scan_files(&payload.path, ext, true, false) {
-        Ok(paths) => match workspace::process_parallel(&paths, workspace::repair_file) {
-            Ok(results) => {
-                let formatted: Vec<Value> = results
-                    .into_iter()
-                    .map(|r| match r {
-                        Ok(m) => json!(m),
-                        Err(e) => json!(format!("ERROR: {}", e)),
-                    })
-                    .collect();
-                Json(ProjectResponse {
-                    status: "Done".into(),
-                    results: formatted,
-                })
-            }
-            Err(e) => Json(ProjectResponse {
-                status: "Error".into(),
-                results: vec![json!(format!("Error de procesamiento: {}", e))],
-            }),
-        },
-        Err(e) => Json(ProjectResponse {
-            status: "Error".into(),
-            results: vec![json!(format!("Error de escaneo: {}", e))],
-        }),
+fn legacy_error(context: &str, err: impl std::fmt::Display) -> ProjectResponse {
+    ProjectResponse {
+        status: "Error".into(),
+        results: vec![json!(format!("{}: {}", context, err))],
     }
 }
 
+/// La API heredada no tiene sesión ni roots del cliente: vale el confinamiento a las raíces del servidor
+fn scan_confined(path: &str, ext: &str) -> crate::error::Result<Vec<PathBuf>> {
+    let root = roots::confine(path, &roots::fallback())?;
+    CodeAnalyzer::scan_files(&root.to_string_lossy(), ext, true, false)
+}
+
 pub async fn ultra_engine_handler(Json(payload): Json<UltraRequest>) -> Json<UltraResponse> {
+    if let Err(e) = roots::confine(&payload.target_dir, &roots::fallback()) {
+        return Json(UltraResponse {
+            status: "Error".into(),
+            engine_output: e.to_string(),
+        });
+    }
     let tasks = payload.max_tasks.unwrap_or(rayon::current_num_threads());
     let dry = payload.dry_run.unwrap_or(false);
     let out = execute_ultra_wsl(
@@ -0,0 +1,374 @@
+//! jobs.rs - Trabajos asíncronos: una tool corre en segundo plano y se consulta por id
+//! `POST /jobs` valida y encola la tool y responde al instante con el id; `GET /jobs/{id}` da
+//! estado, progreso y resultado, `GET /jobs/{id}/events` lo mismo como SSE y `DELETE /jobs/{id}`
+//! cancela uno en curso (o descarta uno terminado). Pasan por la misma validación, permisos y
+//! admisión que `tools/call`. Cada job es de quien lo creó: con autenticación, solo el mismo
+//! token lo ve, lo sigue o lo cancela (para el resto no existe). Los terminados se guardan
+//! `[jobs] ttl_secs`; por MCP se leen con las tools `job_status` y `job_result`.
+
+use crate::config::CONFIG;
+use crate::error::{MemoryPError, Result};
+use crate::mcp::auth::Grant;
+use crate::mcp::models::JsonRpcError;
+use crate::mcp::tools::{self, McpTool, ToolContext};
+use crate::mcp::{admission, metrics, roots, schema, status};
+use crate::parallel_engine::JobMonitor;
+use futures::stream::{self, Stream};
+use lazy_static::lazy_static;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
+
+/// Cada cuánto se buscan jobs vencidos
+const REAPER_INTERVAL: Duration = Duration::from_secs(60);
+/// Como mucho 4 eventos de progreso por segundo y solo si hubo avance
+const EVENT_INTERVAL: Duration = Duration::from_millis(250);
+
+lazy_static! {
+    static ref JOBS: scc::HashMap<String, Arc<Job>> = scc::HashMap::new();
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    /// Esperando turno en la admisión
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    /// Cancelado; si la tool alcanzó a correr, el resultado es parcial
+    Cancelled,
+}
+
+impl JobState {
+    pub fn is_finished(self) -> bool {
+        !matches!(self, JobState::Queued | JobState::Running)
+    }
+}
+
+pub struct Job {
+    pub id: String,
+    pub tool: String,
+    /// Token que lo creó (`None` sin autenticación)
+    grant: Option<Grant>,
+    monitor: Arc<JobMonitor>,
+    state: watch::Sender<JobState>,
+    /// Resultado de la tool (`content` + `structuredContent`) o el error
+    outcome: Mutex<Option<std::result::Result<Value, JsonRpcError>>>,
+    created_at: u64,
+    /// 0 mientras no termina
+    finished_at: AtomicU64,
+}
+
+impl Job {
+    pub fn state(&self) -> JobState {
+        *self.state.borrow()
+    }
+
+    /// ¿Lo creó el mismo token (o ninguno, sin autenticación)?
+    fn is_owned_by(&self, grant: Option<&Grant>) -> bool {
+        self.grant.as_ref().map(|g| &g.name) == grant.map(|g| &g.name)
+    }
+
+    /// Pide la cancelación; la tool la ve en su próxima unidad de trabajo
+    pub fn cancel(&self) {
+        self.monitor.cancel();
+    }
+
+    /// Resultado de la tool si ya terminó: `Ok` también para uno cancelado con resultado parcial
+    pub fn outcome(&self) -> Option<std::result::Result<Value, JsonRpcError>> {
+        self.outcome.lock().ok()?.clone()
+    }
+
+    /// Estado y progreso; con `with_result`, también el resultado o el error si ya terminó
+    pub fn view(&self, with_result: bool) -> Value {
+        let (done, total) = self.monitor.snapshot();
+        let finished_at = self.finished_at.load(Ordering::Relaxed);
+        let mut view = json!({
+            "id": self.id,
+            "tool": self.tool,
+            "status": self.state(),
+            "progress": { "done": done, "total": total },
+            "created_at": self.created_at,
+            "finished_at": (finished_at > 0).then_some(finished_at),
+        });
+        if with_result {
+            match self.outcome() {
+                Some(Ok(result)) => view["result"] = result,
+                Some(Err(error)) => view["error"] = json!(error),
+                None => {}
+            }
+        }
+        view
+    }
+
+    fn finish(&self, state: JobState, outcome: std::result::Result<Value, JsonRpcError>) {
+        if let Ok(mut slot) = self.outcome.lock() {
+            *slot = Some(outcome);
+        }
+        self.finished_at.store(now_secs(), Ordering::Relaxed);
+        self.state.send_replace(state);
+    }
+}
+
+/// Valida `arguments` contra la tool, cobra el límite de tasa de `client` y lanza el job.
+/// Los errores aquí (tool desconocida, argumentos, permisos, tasa) no crean job.
+pub fn start(
+    tool_name: &str,
+    mut arguments: Value,
+    grant: Option<&Grant>,
+    client: &str,
+) -> Result<Arc<Job>> {
+    let tool = tools::get(tool_name)
+        .ok_or_else(|| MemoryPError::InvalidParams(format!("Tool desconocida: {}", tool_name)))?;
+    if let Some(grant) = grant {
+        grant.require(tool.scope())?;
+    }
+    if arguments.is_null() {
+        arguments = json!({});
+    }
+    schema::validate(&tool.input_schema(), &mut arguments)?;
+    admission::check_rate(client)?;
+
+    let job = Arc::new(Job {
+        id: uuid::Uuid::new_v4().to_string(),
+        tool: tool_name.to_string(),
+        grant: grant.cloned(),
+        monitor: Arc::new(JobMonitor::default()),
+        state: watch::Sender::new(JobState::Queued),
+        outcome: Mutex::new(None),
+        created_at: now_secs(),
+        finished_at: AtomicU64::new(0),
+    });
+    let _ = JOBS.insert(job.id.clone(), job.clone());
+    tracing::info!("📋 Job {} encolado: {}", job.id, job.tool);
+    tokio::spawn(run(job.clone(), tool, arguments));
+    Ok(job)
+}
+
+async fn run(job: Arc<Job>, tool: Arc<dyn McpTool>, arguments: Value) {
+    // Sin sesión ni roots del cliente: valen las raíces del servidor, como en HTTP sin sesión
+    let outcome = match admission::acquire().await {
+        Ok(_) if job.monitor.is_cancelled() => {
+            job.finish(
+                JobState::Cancelled,
+                Err(JsonRpcError::from(&MemoryPError::Other(
+                    "Cancelado antes de empezar".to_string(),
+                ))),
+            );
+            return;
+        }
+        Ok(_admission) => {
+            job.state.send_replace(JobState::Running);
+            let ctx = ToolContext {
+                monitor: job.monitor.clone(),
+                roots: Arc::new(roots::fallback()),
+                grant: job.grant.clone(),
+            };
+            let started = Instant::now();
+            let outcome = tool.call(arguments, ctx).await;
+            metrics::record_call(&job.tool, &job.monitor, started.elapsed(), &outcome);
+            outcome
+        }
+        Err(err) => Err(err),
+    };
+
+    match outcome {
+        Ok(result) if job.monitor.is_cancelled() => job.finish(JobState::Cancelled, Ok(result)),
+        Ok(result) => job.finish(JobState::Completed, Ok(result)),
+        Err(err) => {
+            tracing::warn!("⚠️ Job {} ({}) falló: {}", job.id, job.tool, err);
+            status::record_error(&job.tool, &err);
+            job.finish(JobState::Failed, Err(JsonRpcError::from(&err)));
+        }
+    }
+    tracing::info!("📋 Job {} terminado: {:?}", job.id, job.state());
+}
+
+/// Job `id` si es de `grant`; el de otro token se trata como inexistente
+pub fn get(id: &str, grant: Option<&Grant>) -> Option<Arc<Job>> {
+    JOBS.read(id, |_, job| job.clone())
+        .filter(|job| job.is_owned_by(grant))
+}
+
+/// Descarta un job (solo los terminados: uno en curso se cancela primero)
+pub fn remove(id: &str) -> bool {
+    JOBS.remove_if(id, |job| job.state().is_finished())
+        .is_some()
+}
+
+/// Cancela todos los jobs en curso o en cola (apagado); devuelve cuántos
+pub fn cancel_all() -> usize {
+    let mut cancelled = 0;
+    JOBS.scan(|_, job| {
+        if !job.state().is_finished() {
+            job.cancel();
+            cancelled += 1;
+        }
+    });
+    cancelled
+}
+
+/// (en curso o en cola, terminados) para `/status`
+pub fn counts() -> (usize, usize) {
+    let (mut active, mut finished) = (0, 0);
+    JOBS.scan(|_, job| {
+        if job.state().is_finished() {
+            finished += 1;
+        } else {
+            active += 1;
+        }
+    });
+    (active, finished)
+}
+
+/// Eventos SSE de un job: `progress` cada vez que avanza y un `done` final con el resultado
+pub fn events(job: Arc<Job>) -> impl Stream<Item = (&'static str, Value)> {
+    let state = job.state.subscribe();
+    stream::unfold(Some((job, state, None)), |cursor| async move {
+        let (job, mut state, mut last) = cursor?;
+        loop {
+            if job.state().is_finished() {
+                return Some((("done", job.view(true)), None));
+            }
+            let snapshot = Some((job.state(), job.monitor.snapshot()));
+            if snapshot != last {
+                last = snapshot;
+                return Some((("progress", job.view(false)), Some((job, state, last))));
+            }
+            tokio::select! {
+                _ = state.changed() => {}
+                _ = tokio::time::sleep(EVENT_INTERVAL) => {}
+            }
+        }
+    })
+}
+
+/// Descarta periódicamente los jobs terminados hace más de `[jobs] ttl_secs`
+pub fn spawn_reaper() {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(REAPER_INTERVAL);
+        loop {
+            interval.tick().await;
+            reap(now_secs().saturating_sub(CONFIG.jobs.ttl_secs));
+        }
+    });
+}
+
+/// Descarta los jobs terminados antes de `cutoff` (segundos UNIX)
+fn reap(cutoff: u64) {
+    JOBS.retain(|_, job| {
+        let finished_at = job.finished_at.load(Ordering::Relaxed);
+        finished_at == 0 || finished_at >= cutoff
+    });
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::auth::Scope;
+    use futures::future::BoxFuture;
+
+    /// Devuelve `{"echo": arguments}`; con `wait`, espera hasta que lo cancelen.
+    /// Cada test la registra con su propio nombre para no pisarse en el registro global.
+    struct TestTool(&'static str);
+
+    impl McpTool for TestTool {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn description(&self) -> &str {
+            "eco para tests"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({ "type": "object", "properties": { "wait": { "type": "boolean" } } })
+        }
+
+        fn call(&self, arguments: Value, ctx: ToolContext) -> BoxFuture<'static, Result<Value>> {
+            Box::pin(async move {
+                if arguments["wait"].as_bool().unwrap_or(false) {
+                    while !ctx.monitor.is_cancelled() {
+                        tokio::time::sleep(Duration::from_millis(5)).await;
+                    }
+                }
+                Ok(tools::tool_result("eco".to_string(), json!({ "echo": arguments })))
+            })
+        }
+    }
+
+    fn grant(name: &str) -> Grant {
+        Grant {
+            name: name.to_string(),
+            scopes: vec![Scope::Read],
+        }
+    }
+
+    async fn wait_for(job: &Job, pred: impl Fn(JobState) -> bool) {
+        let mut state = job.state.subscribe();
+        let waited = tokio::time::timeout(Duration::from_secs(5), state.wait_for(|s| pred(*s)));
+        assert!(waited.await.is_ok(), "el job no llegó al estado esperado");
+    }
+
+    #[tokio::test]
+    async fn test_create_status_result_for_owner_only() {
+        let _tool = tools::register_scoped(Arc::new(TestTool("test_jobs_owner")));
+        let owner = grant("dueño");
+        let job = start("test_jobs_owner", json!({ "x": 1 }), Some(&owner), "t1").unwrap();
+        wait_for(&job, JobState::is_finished).await;
+
+        assert_eq!(job.state(), JobState::Completed);
+        let view = get(&job.id, Some(&owner)).unwrap().view(true);
+        assert_eq!(view["status"], "completed");
+        assert_eq!(view["result"]["structuredContent"]["echo"]["x"], 1);
+        // Otro token (o ninguno) no lo ve
+        assert!(get(&job.id, Some(&grant("otro"))).is_none());
+        assert!(get(&job.id, None).is_none());
+
+        assert!(start("no_existe", json!({}), None, "t1").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_running_job() {
+        let _tool = tools::register_scoped(Arc::new(TestTool("test_jobs_cancel")));
+        let job = start("test_jobs_cancel", json!({ "wait": true }), None, "t2").unwrap();
+        wait_for(&job, |s| s == JobState::Running).await;
+        // Uno en curso no se descarta: primero se cancela
+        assert!(!remove(&job.id));
+
+        job.cancel();
+        wait_for(&job, JobState::is_finished).await;
+        assert_eq!(job.state(), JobState::Cancelled);
+        assert!(matches!(job.outcome(), Some(Ok(_))));
+        assert!(remove(&job.id));
+        assert!(get(&job.id, None).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reap_drops_only_expired_jobs() {
+        let _tool = tools::register_scoped(Arc::new(TestTool("test_jobs_reap")));
+        let old = start("test_jobs_reap", json!({}), None, "t3").unwrap();
+        let fresh = start("test_jobs_reap", json!({}), None, "t3").unwrap();
+        let running = start("test_jobs_reap", json!({ "wait": true }), None, "t3").unwrap();
+        wait_for(&old, JobState::is_finished).await;
+        wait_for(&fresh, JobState::is_finished).await;
+        old.finished_at.store(100, Ordering::Relaxed);
+
+        reap(now_secs() - 60);
+        assert!(get(&old.id, None).is_none());
+        assert!(get(&fresh.id, None).is_some());
+        assert!(get(&running.id, None).is_some());
+        running.cancel();
+    }
+}
@@ -0,0 +1,192 @@
+//! logging.rs - Capacidad `logging` de MCP (logging/setLevel + notifications/message)
+//! Una capa de `tracing` publica los eventos del servidor en el bus de notificaciones;
+//! cada transporte filtra por el nivel que su cliente pidió antes de reenviarlos.
+
+use crate::error::{MemoryPError, Result};
+use crate::mcp::notifications;
+use serde_json::{json, Map, Value};
+use std::sync::atomic::{AtomicU8, Ordering};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+/// Niveles syslog (RFC 5424) en el orden de MCP, de más a menos verboso
+pub const LEVELS: [&str; 8] = [
+    "debug",
+    "info",
+    "notice",
+    "warning",
+    "error",
+    "critical",
+    "alert",
+    "emergency",
+];
+
+/// Nivel de un cliente que aún no llamó a `logging/setLevel`
+pub const DEFAULT_LEVEL: u8 = 3; // warning
+
+const LOG_METHOD: &str = "notifications/message";
+
+/// Solo se reenvían eventos del propio servidor (no de hyper, tokio...)
+const OWN_TARGET: &str = "memory_p";
+
+/// Eventos del bus mismo: reenviarlos podría realimentar el bus
+const BUS_TARGET: &str = "memory_p::mcp::notifications";
+
+/// Módulos que manejan secretos (tokens, configuración, errores de parseo que citan el TOML):
+/// sus eventos quedan en stderr y nunca llegan a los clientes
+const PRIVATE_TARGETS: [&str; 2] = ["memory_p::config", "memory_p::mcp::auth"];
+
+/// Nivel más verboso pedido por algún cliente: evita formatear eventos que nadie verá
+static MOST_VERBOSE: AtomicU8 = AtomicU8::new(DEFAULT_LEVEL);
+
+/// Nivel para los streams sin sesión (`/mcp/sse` heredado)
+static SESSIONLESS_LEVEL: AtomicU8 = AtomicU8::new(DEFAULT_LEVEL);
+
+/// Índice de un nivel MCP (`"warning"` → 3)
+pub fn parse_level(level: &str) -> Result<u8> {
+    LEVELS
+        .iter()
+        .position(|l| *l == level)
+        .map(|i| i as u8)
+        .ok_or_else(|| {
+            MemoryPError::InvalidParams(format!(
+                "Nivel de log desconocido '{}'; válidos: {}",
+                level,
+                LEVELS.join(", ")
+            ))
+        })
+}
+
+/// Registra que un cliente quiere ver eventos desde `level`
+pub fn note_level(level: u8) {
+    MOST_VERBOSE.fetch_min(level, Ordering::Relaxed);
+}
+
+pub fn sessionless_level() -> u8 {
+    SESSIONLESS_LEVEL.load(Ordering::Relaxed)
+}
+
+pub fn set_sessionless_level(level: u8) {
+    note_level(level);
+    SESSIONLESS_LEVEL.store(level, Ordering::Relaxed);
+}
+
+/// ¿Debe un transporte con nivel `min_level` reenviar este mensaje del bus?
+/// Todo lo que no sea `notifications/message` pasa siempre.
+pub fn should_forward(msg: &Value, min_level: u8) -> bool {
+    if msg.get("method").and_then(|m| m.as_str()) != Some(LOG_METHOD) {
+        return true;
+    }
+    msg.pointer("/params/level")
+        .and_then(|l| l.as_str())
+        .and_then(|l| parse_level(l).ok())
+        .is_some_and(|level| level >= min_level)
+}
+
+/// Solo eventos propios, ni del bus ni de módulos con secretos
+fn is_forwardable(target: &str) -> bool {
+    target.starts_with(OWN_TARGET)
+        && !target.starts_with(BUS_TARGET)
+        && !PRIVATE_TARGETS.iter().any(|t| target.starts_with(t))
+}
+
+fn mcp_level(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 4,
+        Level::WARN => 3,
+        Level::INFO => 1,
+        _ => 0,
+    }
+}
+
+/// Capa de `tracing` que convierte eventos en `notifications/message`
+pub struct McpLogLayer;
+
+impl<S: Subscriber> Layer<S> for McpLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let meta = event.metadata();
+        let target = meta.target();
+        if !is_forwardable(target) {
+            return;
+        }
+        let level = mcp_level(meta.level());
+        if level < MOST_VERBOSE.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let mut fields = FieldCollector::default();
+        event.record(&mut fields);
+        // Solo el mensaje: se envía como texto; con campos extra, como objeto
+        let data = match fields.0.remove("message") {
+            Some(message) if fields.0.is_empty() => message,
+            Some(message) => {
+                fields.0.insert("message".into(), message);
+                Value::Object(fields.0)
+            }
+            None => Value::Object(fields.0),
+        };
+
+        notifications::publish(
+            LOG_METHOD,
+            Some(json!({
+                "level": LEVELS[level as usize],
+                "logger": target,
+                "data": data,
+            })),
+        );
+    }
+}
+
+#[derive(Default)]
+struct FieldCollector(Map<String, Value>);
+
+impl Visit for FieldCollector {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), json!(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().into(), json!(format!("{:?}", value)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_forward_by_level() {
+        let warning =
+            notifications::message(LOG_METHOD, Some(json!({ "level": "warning", "data": "x" })));
+        assert!(should_forward(&warning, parse_level("info").unwrap()));
+        assert!(!should_forward(&warning, parse_level("error").unwrap()));
+        // Otras notificaciones no se filtran por nivel
+        let other = notifications::message("notifications/prompts/list_changed", None);
+        assert!(should_forward(&other, parse_level("emergency").unwrap()));
+        assert!(parse_level("verbose").is_err());
+    }
+
+    #[test]
+    fn test_private_targets_stay_off_the_bus() {
+        assert!(is_forwardable("memory_p::mcp::tools::analyze"));
+        assert!(!is_forwardable("hyper::proto"));
+        assert!(!is_forwardable(BUS_TARGET));
+        assert!(!is_forwardable("memory_p::config"));
+        assert!(!is_forwardable("memory_p::mcp::auth"));
+    }
+}
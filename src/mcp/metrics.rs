@@ -0,0 +1,329 @@
+//! metrics.rs - `GET /metrics` en formato de texto de Prometheus
+//! Por tool: llamadas por resultado, duración, archivos procesados, bytes leídos y latencia por
+//! archivo (la mide el motor en el `JobMonitor` de cada llamada y se acumula aquí al terminar).
+//! Además: errores por variante de `MemoryPError`, aciertos de `ANALYSIS_CACHE`, tools y jobs en
+//! curso, admisión y ocupación de los pools de rayon. Solo se cuentan tools registradas, así
+//! que las etiquetas no crecen con lo que mande un cliente.
+
+use crate::analyzer::CodeAnalyzer;
+use crate::error::{MemoryPError, Result};
+use crate::histogram::{Histogram, FILE_BUCKETS};
+use crate::mcp::{admission, cancellation, jobs, session, status, tools};
+use crate::parallel_engine::{self, JobMonitor};
+use lazy_static::lazy_static;
+use serde_json::Value;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Límites (segundos) de la duración de una llamada completa
+const CALL_BUCKETS: &[f64] = &[0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 300.0];
+
+/// Cómo terminó una llamada a una tool
+#[derive(Debug, Clone, Copy)]
+enum Outcome {
+    Ok,
+    Error,
+    Cancelled,
+}
+
+impl Outcome {
+    fn of(result: &Result<Value>, monitor: &JobMonitor) -> Self {
+        match result {
+            Err(_) => Outcome::Error,
+            Ok(_) if monitor.is_cancelled() => Outcome::Cancelled,
+            Ok(_) => Outcome::Ok,
+        }
+    }
+
+    const ALL: [Outcome; 3] = [Outcome::Ok, Outcome::Error, Outcome::Cancelled];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Outcome::Ok => "ok",
+            Outcome::Error => "error",
+            Outcome::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Acumulados de una tool
+struct ToolMetrics {
+    calls: [AtomicU64; 3],
+    files: AtomicU64,
+    bytes: AtomicU64,
+    call_duration: Histogram,
+    file_duration: Histogram,
+}
+
+impl Default for ToolMetrics {
+    fn default() -> Self {
+        Self {
+            calls: Default::default(),
+            files: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            call_duration: Histogram::new(CALL_BUCKETS),
+            file_duration: Histogram::new(FILE_BUCKETS),
+        }
+    }
+}
+
+lazy_static! {
+    static ref TOOLS: scc::HashMap<String, Arc<ToolMetrics>> = scc::HashMap::new();
+    /// (origen, variante) → errores; origen = tool registrada o `request`
+    static ref ERRORS: scc::HashMap<(String, &'static str), u64> = scc::HashMap::new();
+}
+
+fn tool_metrics(tool: &str) -> Arc<ToolMetrics> {
+    TOOLS
+        .entry(tool.to_string())
+        .or_default()
+        .get()
+        .clone()
+}
+
+/// Cuenta una llamada terminada con lo que el motor dejó en su `monitor`
+pub fn record_call(tool: &str, monitor: &JobMonitor, elapsed: Duration, result: &Result<Value>) {
+    let metrics = tool_metrics(tool);
+    metrics.calls[Outcome::of(result, monitor) as usize].fetch_add(1, Ordering::Relaxed);
+    let (processed, _) = monitor.snapshot();
+    metrics.files.fetch_add(processed as u64, Ordering::Relaxed);
+    metrics.bytes.fetch_add(
+        monitor.total_bytes.load(Ordering::Relaxed) as u64,
+        Ordering::Relaxed,
+    );
+    metrics.call_duration.observe(elapsed);
+    metrics.file_duration.absorb(&monitor.file_latency);
+}
+
+/// Cuenta un error por su variante (lo llama `status::record_error`)
+pub fn record_error(origin: &str, err: &MemoryPError) {
+    let origin = if tools::get(origin).is_some() {
+        origin
+    } else {
+        "request"
+    };
+    *ERRORS
+        .entry((origin.to_string(), err.kind()))
+        .or_insert(0)
+        .get_mut() += 1;
+}
+
+/// Texto de exposición de Prometheus (`text/plain; version=0.0.4`)
+pub fn render() -> String {
+    let mut out = String::with_capacity(8 * 1024);
+
+    let mut tools = Vec::new();
+    TOOLS.scan(|name, metrics| tools.push((name.clone(), metrics.clone())));
+    tools.sort_by(|a, b| a.0.cmp(&b.0));
+
+    header(
+        &mut out,
+        "memory_p_tool_calls_total",
+        "counter",
+        "Llamadas a tools por resultado",
+    );
+    for (name, metrics) in &tools {
+        for outcome in Outcome::ALL {
+            let calls = metrics.calls[outcome as usize].load(Ordering::Relaxed);
+            let _ = writeln!(
+                out,
+                "memory_p_tool_calls_total{{tool=\"{}\",outcome=\"{}\"}} {calls}",
+                escape(name),
+                outcome.as_str()
+            );
+        }
+    }
+    header(
+        &mut out,
+        "memory_p_tool_files_processed_total",
+        "counter",
+        "Archivos procesados por tool",
+    );
+    for (name, metrics) in &tools {
+        let files = metrics.files.load(Ordering::Relaxed);
+        let _ = writeln!(
+            out,
+            "memory_p_tool_files_processed_total{{tool=\"{}\"}} {files}",
+            escape(name)
+        );
+    }
+    header(
+        &mut out,
+        "memory_p_tool_bytes_read_total",
+        "counter",
+        "Bytes leídos por tool",
+    );
+    for (name, metrics) in &tools {
+        let bytes = metrics.bytes.load(Ordering::Relaxed);
+        let _ = writeln!(
+            out,
+            "memory_p_tool_bytes_read_total{{tool=\"{}\"}} {bytes}",
+            escape(name)
+        );
+    }
+    header(
+        &mut out,
+        "memory_p_tool_call_duration_seconds",
+        "histogram",
+        "Duración de cada llamada a una tool",
+    );
+    for (name, metrics) in &tools {
+        let labels = format!("tool=\"{}\"", escape(name));
+        metrics
+            .call_duration
+            .render(&mut out, "memory_p_tool_call_duration_seconds", &labels);
+    }
+    header(
+        &mut out,
+        "memory_p_tool_file_duration_seconds",
+        "histogram",
+        "Latencia por archivo dentro de una tool",
+    );
+    for (name, metrics) in &tools {
+        let labels = format!("tool=\"{}\"", escape(name));
+        metrics
+            .file_duration
+            .render(&mut out, "memory_p_tool_file_duration_seconds", &labels);
+    }
+
+    let mut errors = Vec::new();
+    ERRORS.scan(|(origin, kind), count| errors.push((origin.clone(), *kind, *count)));
+    errors.sort();
+    header(
+        &mut out,
+        "memory_p_errors_total",
+        "counter",
+        "Errores por origen (tool o request) y variante de MemoryPError",
+    );
+    for (origin, kind, count) in &errors {
+        let _ = writeln!(
+            out,
+            "memory_p_errors_total{{origin=\"{}\",kind=\"{kind}\"}} {count}",
+            escape(origin)
+        );
+    }
+
+    let (hits, misses) = CodeAnalyzer::cache_stats();
+    gauge(
+        &mut out,
+        "memory_p_analysis_cache_hits_total",
+        "counter",
+        "Análisis servidos desde ANALYSIS_CACHE",
+        hits,
+    );
+    gauge(
+        &mut out,
+        "memory_p_analysis_cache_misses_total",
+        "counter",
+        "Análisis calculados (no estaban o cambiaron)",
+        misses,
+    );
+    gauge(
+        &mut out,
+        "memory_p_analysis_cache_entries",
+        "gauge",
+        "Archivos en ANALYSIS_CACHE",
+        CodeAnalyzer::cache_entries(),
+    );
+
+    let (jobs_active, jobs_finished) = jobs::counts();
+    let admission = admission::snapshot();
+    gauge(
+        &mut out,
+        "memory_p_tools_in_flight",
+        "gauge",
+        "tools/call en ejecución",
+        cancellation::active(),
+    );
+    header(
+        &mut out,
+        "memory_p_async_jobs",
+        "gauge",
+        "Jobs de /jobs por estado",
+    );
+    let _ = writeln!(out, "memory_p_async_jobs{{state=\"active\"}} {jobs_active}");
+    let _ = writeln!(
+        out,
+        "memory_p_async_jobs{{state=\"finished\"}} {jobs_finished}"
+    );
+    gauge(
+        &mut out,
+        "memory_p_admission_running",
+        "gauge",
+        "Tools con turno de admisión",
+        &admission["running"],
+    );
+    gauge(
+        &mut out,
+        "memory_p_admission_queued",
+        "gauge",
+        "Tools esperando turno",
+        &admission["queued"],
+    );
+    gauge(
+        &mut out,
+        "memory_p_admission_max_concurrent",
+        "gauge",
+        "[limits] max_concurrent_jobs",
+        &admission["max_concurrent_jobs"],
+    );
+
+    let pools = parallel_engine::pool_usage();
+    gauge(
+        &mut out,
+        "memory_p_rayon_threads",
+        "gauge",
+        "Hilos de los pools de rayon vivos",
+        pools.threads,
+    );
+    gauge(
+        &mut out,
+        "memory_p_rayon_busy_threads",
+        "gauge",
+        "Hilos de rayon procesando un archivo ahora",
+        pools.busy,
+    );
+    gauge(
+        &mut out,
+        "memory_p_rayon_busy_seconds_total",
+        "counter",
+        "Tiempo de hilo de rayon dedicado a archivos",
+        pools.busy_micros as f64 / 1e6,
+    );
+
+    gauge(
+        &mut out,
+        "memory_p_sessions",
+        "gauge",
+        "Sesiones MCP abiertas",
+        session::count(),
+    );
+    gauge(
+        &mut out,
+        "memory_p_uptime_seconds",
+        "gauge",
+        "Segundos desde el arranque",
+        status::uptime_secs(),
+    );
+    out
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// Métrica sin etiquetas: cabecera y valor
+fn gauge(out: &mut String, name: &str, kind: &str, help: &str, value: impl std::fmt::Display) {
+    header(out, name, kind, help);
+    let _ = writeln!(out, "{name} {value}");
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -1,3 +1,22 @@
 // [nuclear_god_mode] PROCESSED AT MAX SPEED
-pub mod models;
+pub mod admission;
+pub mod auth;
+pub mod cancellation;
+pub mod completion;
 pub mod handlers;
+pub mod jobs;
+pub mod logging;
+pub mod metrics;
+pub mod models;
+pub mod notifications;
+pub mod origin;
+pub mod progress;
+pub mod prompts;
+pub mod protocol;
+pub mod resources;
+pub mod roots;
+pub mod schema;
+pub mod session;
+pub mod shutdown;
+pub mod status;
+pub mod tools;
@@ -1,4 +1,5 @@
 // [nuclear_god_mode] PROCESSED AT MAX SPEED - STABILITY FIX
+use crate::error::MemoryPError;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -14,8 +15,58 @@ pub struct JsonRpcRequest {
 pub struct JsonRpcResponse {
     pub jsonrpc: String,
     pub id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub result: Option<Value>,
-    pub error: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<JsonRpcError>,
+}
+
+impl JsonRpcResponse {
+    pub fn success(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn failure(id: Value, err: &MemoryPError) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError::from(err)),
+        }
+    }
+}
+
+/// Objeto `error` de JSON-RPC 2.0
+#[derive(Serialize, Debug, Clone)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl From<&MemoryPError> for JsonRpcError {
+    fn from(err: &MemoryPError) -> Self {
+        Self {
+            code: err.rpc_code(),
+            message: err.to_string(),
+            data: Some(err.rpc_data()),
+        }
+    }
+}
+
+/// Mensaje JSON-RPC sin `id`: el servidor lo emite y el cliente no responde
+#[derive(Serialize, Debug, Clone)]
+pub struct JsonRpcNotification {
+    pub jsonrpc: String,
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -26,20 +77,26 @@ pub struct McpDescriptor {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct Tool {
     pub name: String,
     pub description: String,
     pub input_schema: Value,
+    /// Forma de `structuredContent` en el resultado de la tool
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub annotations: Option<Value>,
 }
 
-#[allow(dead_code)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct Resource {
     pub uri: String,
     pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mime_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub annotations: Option<Value>,
@@ -0,0 +1,50 @@
+//! notifications.rs - Bus de notificaciones servidor → cliente
+//! Los transportes (stdio, SSE) se suscriben y reenvían cada mensaje tal cual.
+
+use crate::mcp::models::JsonRpcNotification;
+use lazy_static::lazy_static;
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+/// Mensajes retenidos para suscriptores lentos antes de descartar los más viejos
+const BUS_CAPACITY: usize = 256;
+
+lazy_static! {
+    static ref BUS: broadcast::Sender<Value> = broadcast::channel(BUS_CAPACITY).0;
+}
+
+/// Publica una notificación JSON-RPC a todos los transportes conectados
+pub fn publish(method: &str, params: Option<Value>) {
+    // Sin suscriptores el envío falla: no hay nadie a quien notificar.
+    let _ = BUS.send(message(method, params));
+}
+
+/// Construye el mensaje JSON-RPC de una notificación
+pub fn message(method: &str, params: Option<Value>) -> Value {
+    let msg = JsonRpcNotification {
+        jsonrpc: "2.0".to_string(),
+        method: method.to_string(),
+        params,
+    };
+    serde_json::to_value(msg).unwrap_or(Value::Null)
+}
+
+pub fn subscribe() -> broadcast::Receiver<Value> {
+    BUS.subscribe()
+}
+
+/// Siguiente notificación del bus; salta los mensajes perdidos por retraso
+pub async fn next(rx: &mut broadcast::Receiver<Value>) -> Option<Value> {
+    loop {
+        match rx.recv().await {
+            Ok(msg) => return Some(msg),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!(
+                    "⚠️ Suscriptor lento: {} notificaciones descartadas",
+                    skipped
+                );
+            }
+            Err(broadcast::error::RecvError::Closed) => return None,
+        }
+    }
+}
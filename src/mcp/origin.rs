@@ -0,0 +1,88 @@
+//! origin.rs - Validación de `Origin` y política CORS del servidor HTTP
+//! Un navegador siempre manda `Origin`: si no está en `[http] allowed_origins` (o es localhost
+//! con `allow_localhost`), el request se rechaza con 403 antes de tocar ninguna ruta. Así una
+//! página cualquiera no puede hablar con el servidor local vía DNS rebinding. Los clientes que no
+//! son navegador no mandan `Origin` y pasan. La capa CORS responde los preflight de los mismos
+//! orígenes, para que el dashboard y herramientas web de confianza llamen a `/mcp` a propósito.
+
+use crate::config::CONFIG;
+use crate::error::MemoryPError;
+use crate::mcp::{auth, protocol, session};
+use axum::{
+    extract::Request,
+    http::{header, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+/// Middleware de `mcp_api::routes`: corta los requests de navegador de orígenes desconocidos
+pub async fn validate(req: Request, next: Next) -> Response {
+    if let Some(origin) = req.headers().get(header::ORIGIN) {
+        if !is_allowed(origin) {
+            let origin = origin.to_str().unwrap_or("<no ASCII>");
+            tracing::warn!("🚫 Origin rechazado: {} ({})", origin, req.uri().path());
+            let err = MemoryPError::Forbidden(format!("Origin no permitido: {}", origin));
+            return auth::reject(StatusCode::FORBIDDEN, &err);
+        }
+    }
+    next.run(req).await
+}
+
+/// CORS para los orígenes permitidos, con las cabeceras del transporte MCP
+pub fn cors() -> CorsLayer {
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::predicate(|origin, _| is_allowed(origin)))
+        .allow_methods([Method::GET, Method::POST, Method::DELETE])
+        .allow_headers([
+            header::CONTENT_TYPE,
+            header::AUTHORIZATION,
+            header::ACCEPT,
+            HeaderName::from_static(session::SESSION_HEADER),
+            HeaderName::from_static(protocol::VERSION_HEADER),
+            HeaderName::from_static("last-event-id"),
+        ])
+        .expose_headers([HeaderName::from_static(session::SESSION_HEADER)])
+}
+
+fn is_allowed(origin: &HeaderValue) -> bool {
+    let Ok(origin) = origin.to_str() else {
+        return false;
+    };
+    let http = &CONFIG.http;
+    http.allowed_origins
+        .iter()
+        .any(|allowed| allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+        || (http.allow_localhost && is_localhost(origin))
+}
+
+/// `http(s)://localhost`, `127.0.0.1` o `[::1]`, con o sin puerto
+fn is_localhost(origin: &str) -> bool {
+    let Some((scheme, authority)) = origin.split_once("://") else {
+        return false;
+    };
+    if !matches!(scheme, "http" | "https") {
+        return false;
+    }
+    let host = match authority.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or(""),
+        None => authority.split(':').next().unwrap_or(""),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_localhost_origins() {
+        assert!(is_localhost("http://localhost:3000"));
+        assert!(is_localhost("http://127.0.0.1"));
+        assert!(is_localhost("https://[::1]:4040"));
+        assert!(!is_localhost("http://localhost.evil.com"));
+        assert!(!is_localhost("http://127.0.0.1.nip.io:4040"));
+        assert!(!is_localhost("null"));
+        assert!(!is_localhost("file://localhost"));
+    }
+}
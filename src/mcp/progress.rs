@@ -0,0 +1,116 @@
+//! progress.rs - `notifications/progress` para tools de larga duración
+//! Si el `tools/call` trae `_meta.progressToken`, un reporter lee el `JobMonitor` del motor
+//! a intervalos fijos y emite el avance por el canal del request (stdio o stream SSE del POST).
+
+use crate::mcp::notifications;
+use crate::parallel_engine::JobMonitor;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+
+/// Como mucho 4 notificaciones por segundo y solo si hubo avance
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// `params._meta.progressToken` (string o número) de un request
+pub fn token(params: &Value) -> Option<Value> {
+    params
+        .get("_meta")
+        .and_then(|m| m.get("progressToken"))
+        .filter(|t| t.is_string() || t.is_number())
+        .cloned()
+}
+
+pub struct ProgressReporter {
+    done: watch::Sender<bool>,
+    handle: JoinHandle<()>,
+}
+
+impl ProgressReporter {
+    /// Empieza a reportar el avance de `monitor`; `unit` nombra lo que se cuenta
+    pub fn spawn(
+        token: Value,
+        monitor: Arc<JobMonitor>,
+        sink: mpsc::UnboundedSender<Value>,
+        unit: &'static str,
+    ) -> Self {
+        let (done, mut finished) = watch::channel(false);
+        let handle = tokio::spawn(async move {
+            let mut last = None;
+            let mut interval = tokio::time::interval(PROGRESS_INTERVAL);
+            loop {
+                let is_final = tokio::select! {
+                    _ = interval.tick() => false,
+                    _ = finished.changed() => true,
+                };
+                let (processed, total) = monitor.snapshot();
+                // Mientras se escanea no hay total: nada útil que reportar todavía
+                if last != Some((processed, total)) && (total > 0 || processed > 0) {
+                    last = Some((processed, total));
+                    let msg = notification(&token, processed, total, unit);
+                    if sink.send(msg).is_err() {
+                        break;
+                    }
+                }
+                if is_final {
+                    break;
+                }
+            }
+        });
+        Self { done, handle }
+    }
+
+    /// Emite el último avance pendiente y espera al reporter (antes de enviar la respuesta)
+    pub async fn finish(self) {
+        let _ = self.done.send(true);
+        let _ = self.handle.await;
+    }
+}
+
+fn notification(token: &Value, processed: usize, total: usize, unit: &str) -> Value {
+    let mut params = json!({
+        "progressToken": token,
+        "progress": processed,
+        "message": format!("{}/{} {}", processed, total, unit),
+    });
+    if total > 0 {
+        params["total"] = json!(total);
+    }
+    notifications::message("notifications/progress", Some(params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_from_meta() {
+        assert_eq!(
+            token(&json!({ "_meta": { "progressToken": 7 } })),
+            Some(json!(7))
+        );
+        assert_eq!(token(&json!({ "_meta": { "progressToken": {} } })), None);
+        assert_eq!(token(&json!({ "name": "analyze" })), None);
+    }
+
+    #[tokio::test]
+    async fn test_finish_flushes_last_progress() {
+        let monitor = Arc::new(JobMonitor::default());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let reporter = ProgressReporter::spawn(json!("t"), monitor.clone(), tx, "archivos");
+        monitor.add_total(3);
+        monitor
+            .processed
+            .fetch_add(3, std::sync::atomic::Ordering::Relaxed);
+        reporter.finish().await;
+
+        let mut last = None;
+        while let Ok(msg) = rx.try_recv() {
+            last = Some(msg);
+        }
+        let params = &last.unwrap()["params"];
+        assert_eq!(params["progress"], 3);
+        assert_eq!(params["total"], 3);
+    }
+}
@@ -0,0 +1,346 @@
+//! prompts.rs - Prompts MCP (prompts/list, prompts/get)
+//! Sirve los procedimientos en markdown del repo (`.windsurf/workflows`, `.agent/rules`...)
+//! como prompts, con argumentos declarados en el front-matter y sustitución `{{arg}}`.
+//!
+//! Front-matter soportado:
+//! ```text
+//! ---
+//! description: Depuración segura
+//! arguments:
+//!   - name: archivo
+//!     description: Archivo a depurar
+//!     required: true
+//! ---
+//! ```
+
+use crate::error::{MemoryPError, Result};
+use crate::mcp::notifications;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Directorios donde el equipo guarda procedimientos reutilizables
+const PROMPT_DIRS: &[&str] = &[
+    ".windsurf/workflows",
+    ".windsurf/rules",
+    ".agent/workflows",
+    ".agent/rules",
+];
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PromptArgument {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub required: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Prompt {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub arguments: Vec<PromptArgument>,
+    #[serde(skip)]
+    pub template: String,
+}
+
+/// Descubre todos los prompts disponibles (ordenados por nombre)
+pub fn list() -> Vec<Prompt> {
+    let mut prompts: Vec<Prompt> = Vec::new();
+    for path in prompt_files() {
+        let Ok(raw) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let prompt = parse_prompt(&path, &raw);
+        if prompts.iter().any(|p| p.name == prompt.name) {
+            tracing::warn!(
+                "⚠️ Prompt duplicado '{}' en {}, se ignora",
+                prompt.name,
+                path.display()
+            );
+            continue;
+        }
+        prompts.push(prompt);
+    }
+    prompts.sort_by(|a, b| a.name.cmp(&b.name));
+    prompts
+}
+
+/// Renderiza un prompt en el formato `prompts/get`
+pub fn get(name: &str, arguments: &HashMap<String, String>) -> Result<Value> {
+    let prompt = list()
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| MemoryPError::InvalidParams(format!("Prompt no encontrado: {}", name)))?;
+
+    let missing: Vec<&str> = prompt
+        .arguments
+        .iter()
+        .filter(|a| a.required && !arguments.contains_key(&a.name))
+        .map(|a| a.name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(MemoryPError::InvalidParams(format!(
+            "Faltan argumentos requeridos para '{}': {}",
+            name,
+            missing.join(", ")
+        )));
+    }
+
+    Ok(json!({
+        "description": prompt.description,
+        "messages": [{
+            "role": "user",
+            "content": { "type": "text", "text": render(&prompt.template, arguments) }
+        }]
+    }))
+}
+
+/// Emite `notifications/prompts/list_changed` cuando cambian los markdown
+pub fn spawn_watcher() {
+    tokio::spawn(async move {
+        let mut known = snapshot();
+        let mut interval = tokio::time::interval(WATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let current = match tokio::task::spawn_blocking(snapshot).await {
+                Ok(s) => s,
+                Err(_) => continue,
+            };
+            if current != known {
+                notifications::publish("notifications/prompts/list_changed", None);
+                known = current;
+            }
+        }
+    });
+}
+
+fn snapshot() -> Vec<(PathBuf, Option<SystemTime>)> {
+    prompt_files()
+        .into_iter()
+        .map(|p| {
+            let modified = fs::metadata(&p).and_then(|m| m.modified()).ok();
+            (p, modified)
+        })
+        .collect()
+}
+
+fn prompt_files() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = PROMPT_DIRS
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()))
+        .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "md"))
+        .collect();
+    files.sort();
+    files
+}
+
+fn parse_prompt(path: &Path, raw: &str) -> Prompt {
+    let name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let (front, body) = split_front_matter(raw);
+    let (mut description, mut arguments) = parse_front_matter(front);
+
+    // Placeholders usados en el cuerpo pero no declarados: argumentos opcionales
+    for placeholder in placeholders(body) {
+        if !arguments.iter().any(|a| a.name == placeholder) {
+            arguments.push(PromptArgument {
+                name: placeholder,
+                description: None,
+                required: false,
+            });
+        }
+    }
+
+    if description.is_none() {
+        description = body
+            .lines()
+            .find(|l| l.starts_with('#'))
+            .map(|l| l.trim_start_matches('#').trim().to_string());
+    }
+
+    Prompt {
+        name,
+        description,
+        arguments,
+        template: body.trim().to_string(),
+    }
+}
+
+/// Separa el bloque `---` inicial del cuerpo del markdown
+fn split_front_matter(raw: &str) -> (&str, &str) {
+    let trimmed = raw.trim_start_matches('\u{feff}');
+    let Some(rest) = trimmed
+        .strip_prefix("---\r\n")
+        .or_else(|| trimmed.strip_prefix("---\n"))
+    else {
+        return ("", trimmed);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (&rest[..offset], &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    ("", trimmed)
+}
+
+/// Subconjunto de YAML: `description:` y la lista `arguments:`
+fn parse_front_matter(front: &str) -> (Option<String>, Vec<PromptArgument>) {
+    let mut description = None;
+    let mut arguments: Vec<PromptArgument> = Vec::new();
+    let mut in_arguments = false;
+
+    for line in front.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        // YAML admite los items de la lista sin sangría bajo `arguments:`
+        let indented = line.starts_with(' ')
+            || line.starts_with('\t')
+            || (in_arguments && line.starts_with("- "));
+        if !indented {
+            in_arguments = false;
+            if let Some((key, value)) = line.split_once(':') {
+                match key.trim() {
+                    "description" => description = Some(unquote(value)),
+                    "arguments" => in_arguments = true,
+                    _ => {}
+                }
+            }
+            continue;
+        }
+        if !in_arguments {
+            continue;
+        }
+
+        let mut entry = line.trim();
+        if let Some(item) = entry.strip_prefix("- ") {
+            arguments.push(PromptArgument {
+                name: String::new(),
+                description: None,
+                required: false,
+            });
+            entry = item.trim();
+        }
+        let (Some(arg), Some((key, value))) = (arguments.last_mut(), entry.split_once(':')) else {
+            continue;
+        };
+        match key.trim() {
+            "name" => arg.name = unquote(value),
+            "description" => arg.description = Some(unquote(value)),
+            "required" => arg.required = value.trim() == "true",
+            _ => {}
+        }
+    }
+
+    arguments.retain(|a| !a.name.is_empty());
+    (description, arguments)
+}
+
+fn unquote(value: &str) -> String {
+    value
+        .trim()
+        .trim_matches('"')
+        .trim_matches('\'')
+        .to_string()
+}
+
+/// Nombres `{{arg}}` presentes en la plantilla, en orden de aparición
+fn placeholders(template: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        let name = after[..end].trim();
+        if is_placeholder(name) && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+        rest = &after[end + 2..];
+    }
+    names
+}
+
+fn is_placeholder(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Sustituye `{{arg}}` (con o sin espacios internos) por su valor, en una sola pasada sobre la
+/// plantilla: un valor que contenga `{{otro}}` queda tal cual y no se vuelve a expandir
+fn render(template: &str, arguments: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let name = after[..end].trim();
+        if is_placeholder(name) {
+            out.push_str(arguments.get(name).map(String::as_str).unwrap_or(""));
+        } else {
+            out.push_str(&rest[start..start + end + 4]);
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_front_matter_arguments() {
+        let raw = "---\ndescription: \"Depurar\"\narguments:\n  - name: archivo\n    description: Archivo\n    required: true\n  - name: nivel\n---\nRevisar {{archivo}} con {{ nivel }}\n";
+        let prompt = parse_prompt(Path::new("debug.md"), raw);
+        assert_eq!(prompt.name, "debug");
+        assert_eq!(prompt.description.as_deref(), Some("Depurar"));
+        assert_eq!(prompt.arguments.len(), 2);
+        assert!(prompt.arguments[0].required);
+        assert!(!prompt.arguments[1].required);
+
+        let args = HashMap::from([
+            ("archivo".to_string(), "main.rs".to_string()),
+            ("nivel".to_string(), "alto".to_string()),
+        ]);
+        assert_eq!(render(&prompt.template, &args), "Revisar main.rs con alto");
+    }
+
+    #[test]
+    fn test_render_does_not_expand_argument_values() {
+        let args = HashMap::from([
+            ("a".to_string(), "{{b}}".to_string()),
+            ("b".to_string(), "secreto".to_string()),
+        ]);
+        assert_eq!(
+            render("{{a}} / {{ b }} / {{x y}} / {{", &args),
+            "{{b}} / secreto / {{x y}} / {{"
+        );
+    }
+
+    #[test]
+    fn test_undeclared_placeholder_becomes_optional_argument() {
+        let prompt = parse_prompt(Path::new("x.md"), "Sin front-matter {{ruta}}");
+        assert_eq!(prompt.arguments[0].name, "ruta");
+        assert!(!prompt.arguments[0].required);
+    }
+}
@@ -0,0 +1,54 @@
+//! protocol.rs - Revisiones del protocolo MCP soportadas y qué cambia entre ellas
+//! `initialize` negocia la versión; el resto del servidor consulta estas funciones
+//! en lugar de comparar fechas a mano.
+
+/// De la más nueva a la más vieja
+pub const SUPPORTED_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+pub const LATEST_VERSION: &str = SUPPORTED_VERSIONS[0];
+
+/// Versión que se asume en HTTP sin sesión ni cabecera `MCP-Protocol-Version`
+pub const DEFAULT_HTTP_VERSION: &str = "2025-03-26";
+
+pub const VERSION_HEADER: &str = "mcp-protocol-version";
+
+/// Versión a usar con un cliente: la pedida si la soportamos, si no la más nueva
+pub fn negotiate(requested: Option<&str>) -> &'static str {
+    requested
+        .and_then(|v| SUPPORTED_VERSIONS.iter().find(|s| **s == v))
+        .copied()
+        .unwrap_or(LATEST_VERSION)
+}
+
+pub fn is_supported(version: &str) -> bool {
+    SUPPORTED_VERSIONS.contains(&version)
+}
+
+/// `annotations` en las tools (2025-03-26+)
+pub fn has_tool_annotations(version: &str) -> bool {
+    version >= "2025-03-26"
+}
+
+/// `outputSchema` / `structuredContent` (2025-06-18+)
+pub fn has_structured_output(version: &str) -> bool {
+    version >= "2025-06-18"
+}
+
+/// Batches JSON-RPC (retirados en 2025-06-18)
+pub fn has_batches(version: &str) -> bool {
+    version < "2025-06-18"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate(Some("2024-11-05")), "2024-11-05");
+        assert_eq!(negotiate(Some("2099-01-01")), LATEST_VERSION);
+        assert_eq!(negotiate(None), LATEST_VERSION);
+        assert!(!has_tool_annotations("2024-11-05"));
+        assert!(has_structured_output("2025-06-18"));
+        assert!(!has_batches("2025-06-18"));
+    }
+}
@@ -0,0 +1,422 @@
+//! resources.rs - Recursos MCP (resources/list, resources/read, resources/subscribe)
+//! Expone con URIs estables:
+//! - `memory-p://analysis/{proyecto}`: último reporte de análisis de cada proyecto
+//! - `memory-p://simulations/phase{N}`: resultados `phaseN_results.json` de `simulate`
+//! - `memory-p://payload/{ruta}`: archivos bajo `PAYLOAD_BANK/`
+
+use crate::error::{MemoryPError, Result};
+use crate::mcp::models::Resource;
+use crate::mcp::notifications;
+use crate::mcp::session::Session;
+use crate::parallel_engine::{ProcessingResult, ProcessingStats};
+use jwalk::WalkDir;
+use lazy_static::lazy_static;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const ANALYSIS_PREFIX: &str = "memory-p://analysis/";
+const SIMULATION_PREFIX: &str = "memory-p://simulations/phase";
+const PAYLOAD_PREFIX: &str = "memory-p://payload/";
+
+const PAYLOAD_DIR: &str = "PAYLOAD_BANK";
+const SIMULATION_PHASES: [u8; 3] = [1, 2, 3];
+
+/// Intervalo de sondeo de los recursos respaldados por archivos
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+/// Reportes de análisis retenidos en memoria; al pasarse se descarta el más viejo
+const ANALYSIS_REPORTS_LIMIT: usize = 32;
+
+lazy_static! {
+    /// Último reporte de análisis por proyecto (clave: ruta canónica) con su orden de llegada
+    static ref ANALYSIS_REPORTS: scc::HashMap<String, (u64, Value)> = scc::HashMap::new();
+    /// Suscripciones activas (resources/subscribe) por (sesión, URI)
+    static ref SUBSCRIPTIONS: scc::HashMap<(String, String), Weak<Session>> = scc::HashMap::new();
+}
+
+static NEXT_REPORT: AtomicU64 = AtomicU64::new(0);
+
+/// Recurso resuelto a partir de su URI
+enum ResourceKind {
+    Analysis(String),
+    Simulation(u8),
+    Payload(PathBuf),
+}
+
+impl ResourceKind {
+    fn parse(uri: &str) -> Result<Self> {
+        if let Some(encoded) = uri.strip_prefix(ANALYSIS_PREFIX) {
+            return Ok(Self::Analysis(decode_component(encoded)?));
+        }
+        if let Some(phase) = uri.strip_prefix(SIMULATION_PREFIX) {
+            return match phase.parse::<u8>() {
+                Ok(p) if SIMULATION_PHASES.contains(&p) => Ok(Self::Simulation(p)),
+                _ => Err(MemoryPError::InvalidParams(format!(
+                    "Fase de simulación desconocida en URI: {}",
+                    uri
+                ))),
+            };
+        }
+        if let Some(rel) = uri.strip_prefix(PAYLOAD_PREFIX) {
+            let rel = PathBuf::from(decode_component(rel)?);
+            // Solo rutas relativas dentro del banco: nada de `..` ni raíces absolutas
+            if rel.components().any(|c| !matches!(c, Component::Normal(_))) {
+                return Err(MemoryPError::InvalidParams(format!(
+                    "Ruta fuera de {}: {}",
+                    PAYLOAD_DIR, uri
+                )));
+            }
+            return Ok(Self::Payload(Path::new(PAYLOAD_DIR).join(rel)));
+        }
+        Err(MemoryPError::InvalidParams(format!(
+            "URI de recurso no soportada: {}",
+            uri
+        )))
+    }
+}
+
+/// Lista todos los recursos disponibles en este momento
+pub fn list() -> Vec<Resource> {
+    let mut resources = Vec::new();
+
+    let mut projects = Vec::new();
+    ANALYSIS_REPORTS.scan(|project, _| projects.push(project.clone()));
+    projects.sort();
+    for project in projects {
+        resources.push(Resource {
+            uri: analysis_uri(&project),
+            name: format!("analysis: {}", project),
+            description: Some(format!("Último reporte de análisis de {}", project)),
+            mime_type: Some("application/json".into()),
+            annotations: None,
+        });
+    }
+
+    for phase in SIMULATION_PHASES {
+        if simulation_path(phase).is_file() {
+            resources.push(Resource {
+                uri: format!("{}{}", SIMULATION_PREFIX, phase),
+                name: format!("phase{}_results.json", phase),
+                description: Some(format!("Resultados de la simulación Phase {}", phase)),
+                mime_type: Some("application/json".into()),
+                annotations: None,
+            });
+        }
+    }
+
+    for path in payload_files() {
+        let rel = path.strip_prefix(PAYLOAD_DIR).unwrap_or(&path);
+        resources.push(Resource {
+            uri: payload_uri(rel),
+            name: rel.to_string_lossy().replace('\\', "/"),
+            description: None,
+            mime_type: Some(mime_for(&path).into()),
+            annotations: None,
+        });
+    }
+
+    resources
+}
+
+/// Lee el contenido de un recurso en el formato `resources/read`
+pub fn read(uri: &str) -> Result<Value> {
+    let (text, mime) = match ResourceKind::parse(uri)? {
+        ResourceKind::Analysis(project) => {
+            let report = ANALYSIS_REPORTS
+                .read(&project, |_, (_, v)| v.clone())
+                .ok_or_else(|| MemoryPError::FileNotFound(PathBuf::from(&project)))?;
+            (serde_json::to_string_pretty(&report)?, "application/json")
+        }
+        ResourceKind::Simulation(phase) => {
+            let path = simulation_path(phase);
+            if !path.is_file() {
+                return Err(MemoryPError::FileNotFound(path));
+            }
+            (fs::read_to_string(&path)?, "application/json")
+        }
+        ResourceKind::Payload(path) => {
+            if !path.is_file() {
+                return Err(MemoryPError::FileNotFound(path));
+            }
+            let text = fs::read_to_string(&path)?;
+            (text, mime_for(&path))
+        }
+    };
+
+    Ok(json!({
+        "contents": [{ "uri": uri, "mimeType": mime, "text": text }]
+    }))
+}
+
+/// Suscribe a la sesión a los cambios de `uri`; solo ella recibe los `updated`
+pub fn subscribe(uri: &str, session: Option<&Arc<Session>>) -> Result<()> {
+    // Validar la URI; el recurso puede aparecer después (p.ej. un reporte futuro)
+    ResourceKind::parse(uri)?;
+    let session = session.ok_or_else(|| {
+        MemoryPError::InvalidParams("resources/subscribe requiere una sesión MCP".into())
+    })?;
+    let _ = SUBSCRIPTIONS.insert(
+        (session.id.clone(), uri.to_string()),
+        Arc::downgrade(session),
+    );
+    Ok(())
+}
+
+/// Quita solo la suscripción de esta sesión; las de otros clientes siguen activas
+pub fn unsubscribe(uri: &str, session: Option<&Arc<Session>>) {
+    if let Some(session) = session {
+        SUBSCRIPTIONS.remove(&(session.id.clone(), uri.to_string()));
+    }
+}
+
+/// Guarda el último reporte de análisis de un proyecto y notifica a los suscriptores
+pub fn record_analysis(
+    project: &str,
+    mode: &str,
+    extension: &str,
+    results: &[ProcessingResult],
+    stats: &ProcessingStats,
+) {
+    let key = fs::canonicalize(project)
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| project.to_string());
+    let generated_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let report = json!({
+        "project": key,
+        "mode": mode,
+        "extension": extension,
+        "generated_at": generated_at,
+        "stats": stats,
+        "results": results,
+    });
+
+    let seq = NEXT_REPORT.fetch_add(1, Ordering::Relaxed);
+    let is_new = ANALYSIS_REPORTS.upsert(key.clone(), (seq, report)).is_none();
+    if is_new {
+        evict_oldest_reports();
+        notifications::publish("notifications/resources/list_changed", None);
+    }
+    notify_updated(&analysis_uri(&key));
+}
+
+/// Mantiene `ANALYSIS_REPORTS` en `ANALYSIS_REPORTS_LIMIT` quitando los reportes más viejos
+fn evict_oldest_reports() {
+    while ANALYSIS_REPORTS.len() > ANALYSIS_REPORTS_LIMIT {
+        let mut oldest: Option<(u64, String)> = None;
+        ANALYSIS_REPORTS.scan(|project, (seq, _)| {
+            if oldest.as_ref().is_none_or(|(s, _)| seq < s) {
+                oldest = Some((*seq, project.clone()));
+            }
+        });
+        match oldest {
+            Some((seq, project)) => {
+                ANALYSIS_REPORTS.remove_if(&project, |(s, _)| *s == seq);
+            }
+            None => break,
+        }
+    }
+}
+
+/// Sondea los recursos en disco y emite `updated` / `list_changed` cuando cambian
+pub fn spawn_watcher() {
+    tokio::spawn(async move {
+        let mut known = file_snapshot();
+        let mut interval = tokio::time::interval(WATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let current = match tokio::task::spawn_blocking(file_snapshot).await {
+                Ok(snapshot) => snapshot,
+                Err(_) => continue,
+            };
+
+            for (uri, modified) in &current {
+                if known.get(uri).is_some_and(|prev| prev != modified) {
+                    notify_updated(uri);
+                }
+            }
+            let added = current.keys().any(|uri| !known.contains_key(uri));
+            let removed = known.keys().any(|uri| !current.contains_key(uri));
+            if added || removed {
+                notifications::publish("notifications/resources/list_changed", None);
+                // Un archivo recreado también cuenta como actualización
+                for uri in current.keys().filter(|uri| !known.contains_key(*uri)) {
+                    notify_updated(uri);
+                }
+            }
+            known = current;
+        }
+    });
+}
+
+/// Envía `notifications/resources/updated` solo a las sesiones suscritas a `uri`
+fn notify_updated(uri: &str) {
+    let mut subscribers = Vec::new();
+    SUBSCRIPTIONS.retain(|(_, subscribed), session| match session.upgrade() {
+        Some(session) => {
+            if subscribed == uri {
+                subscribers.push(session);
+            }
+            true
+        }
+        // La sesión ya terminó: su suscripción se descarta
+        None => false,
+    });
+    for session in subscribers {
+        session.send(notifications::message(
+            "notifications/resources/updated",
+            Some(json!({ "uri": uri })),
+        ));
+    }
+}
+
+/// URI → fecha de modificación de los recursos respaldados por archivos
+fn file_snapshot() -> HashMap<String, SystemTime> {
+    let mut snapshot = HashMap::new();
+    for phase in SIMULATION_PHASES {
+        if let Ok(modified) = fs::metadata(simulation_path(phase)).and_then(|m| m.modified()) {
+            snapshot.insert(format!("{}{}", SIMULATION_PREFIX, phase), modified);
+        }
+    }
+    for path in payload_files() {
+        if let Ok(modified) = fs::metadata(&path).and_then(|m| m.modified()) {
+            let rel = path.strip_prefix(PAYLOAD_DIR).unwrap_or(&path);
+            snapshot.insert(payload_uri(rel), modified);
+        }
+    }
+    snapshot
+}
+
+fn payload_files() -> Vec<PathBuf> {
+    if !Path::new(PAYLOAD_DIR).is_dir() {
+        return Vec::new();
+    }
+    WalkDir::new(PAYLOAD_DIR)
+        .sort(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.path())
+        .collect()
+}
+
+fn simulation_path(phase: u8) -> PathBuf {
+    PathBuf::from(format!("phase{}_results.json", phase))
+}
+
+fn analysis_uri(project: &str) -> String {
+    format!("{}{}", ANALYSIS_PREFIX, encode_component(project))
+}
+
+fn payload_uri(rel: &Path) -> String {
+    let rel = rel.to_string_lossy().replace('\\', "/");
+    let encoded: Vec<String> = rel.split('/').map(encode_component).collect();
+    format!("{}{}", PAYLOAD_PREFIX, encoded.join("/"))
+}
+
+fn mime_for(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => "application/json",
+        Some("md") => "text/markdown",
+        Some("toml") => "application/toml",
+        _ => "text/plain",
+    }
+}
+
+/// Percent-encoding de todo lo que no sea "unreserved" (RFC 3986)
+fn encode_component(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    for b in raw.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+pub(crate) fn decode_component(encoded: &str) -> Result<String> {
+    let bytes = encoded.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = encoded
+                .get(i + 1..i + 3)
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+                .ok_or_else(|| {
+                    MemoryPError::InvalidParams(format!("Percent-encoding inválido: {}", encoded))
+                })?;
+            out.push(hex);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out)
+        .map_err(|_| MemoryPError::InvalidParams(format!("URI no es UTF-8: {}", encoded)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_roundtrip() {
+        let raw = r"C:\Users\dev\mi proyecto/src";
+        let encoded = encode_component(raw);
+        assert!(!encoded.contains('/') && !encoded.contains(' '));
+        assert_eq!(decode_component(&encoded).unwrap(), raw);
+    }
+
+    #[test]
+    fn test_payload_uri_rejects_traversal() {
+        assert!(ResourceKind::parse("memory-p://payload/..%2FCargo.toml").is_err());
+        assert!(ResourceKind::parse("memory-p://payload/WORKFLOWS/demo_workflow.json").is_ok());
+        assert!(ResourceKind::parse("memory-p://simulations/phase7").is_err());
+    }
+
+    #[test]
+    fn test_analysis_reports_evict_oldest() {
+        let stats = ProcessingStats::default();
+        let project = |i: usize| format!("memory_p_evict_{}_{}", std::process::id(), i);
+        for i in 0..=ANALYSIS_REPORTS_LIMIT {
+            record_analysis(&project(i), "deep", "rs", &[], &stats);
+        }
+        assert!(!ANALYSIS_REPORTS.contains(&project(0)));
+        assert!(ANALYSIS_REPORTS.contains(&project(ANALYSIS_REPORTS_LIMIT)));
+    }
+
+    #[test]
+    fn test_updated_only_reaches_subscribed_sessions() {
+        let uri = "memory-p://simulations/phase2";
+        let a = Session::standalone();
+        let b = Session::standalone();
+        let mut a_events = a.subscribe();
+        let mut b_events = b.subscribe();
+
+        subscribe(uri, Some(&a)).unwrap();
+        subscribe(uri, Some(&b)).unwrap();
+        unsubscribe(uri, Some(&b));
+        assert!(subscribe(uri, None).is_err());
+
+        notify_updated(uri);
+        let event = a_events.try_recv().unwrap();
+        assert_eq!(event.message["method"], "notifications/resources/updated");
+        assert_eq!(event.message["params"]["uri"], uri);
+        assert!(b_events.try_recv().is_err());
+
+        let key = (a.id.clone(), uri.to_string());
+        drop(a);
+        notify_updated(uri);
+        assert!(!SUBSCRIPTIONS.contains(&key));
+    }
+}
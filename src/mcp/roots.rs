@@ -0,0 +1,164 @@
+//! roots.rs - Raíces del cliente (`roots/list`) y confinamiento de rutas
+//! Si el cliente declara la capacidad `roots`, se le pide la lista tras `notifications/initialized`
+//! y en cada `notifications/roots/list_changed`. Sin esa capacidad (o sin sesión) valen las
+//! raíces de `[workspace]` en memory_p.toml, o el directorio actual del servidor.
+//! Toda ruta que llega a una tool se canonicaliza (symlinks incluidos) y debe caer dentro.
+
+use crate::config::CONFIG;
+use crate::error::{MemoryPError, Result};
+use crate::mcp::resources::decode_component;
+use crate::mcp::session::Session;
+use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Espera máxima por la respuesta de `roots/list`
+const ROOTS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pide `roots/list` al cliente y guarda las raíces canonicalizadas en la sesión
+pub async fn refresh(session: Arc<Session>) {
+    session.set_roots(None);
+    match session.request("roots/list", None, ROOTS_TIMEOUT).await {
+        Ok(result) => {
+            let roots = parse(&result);
+            tracing::info!(
+                "📁 Raíces del cliente: {}",
+                roots
+                    .iter()
+                    .map(|r| r.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            session.set_roots(Some(roots));
+        }
+        Err(e) => {
+            tracing::warn!(
+                "⚠️ roots/list falló, se usan las raíces del servidor: {}",
+                e
+            );
+            session.set_roots(Some(fallback()));
+        }
+    }
+}
+
+/// Raíces vigentes para un request; espera una lista pendiente hasta `ROOTS_TIMEOUT`
+pub async fn for_session(session: Option<&Arc<Session>>) -> Arc<Vec<PathBuf>> {
+    let Some(session) = session.filter(|s| s.client_supports("roots")) else {
+        return Arc::new(fallback());
+    };
+    let mut roots = session.roots();
+    let ready = tokio::time::timeout(ROOTS_TIMEOUT, roots.wait_for(|r| r.is_some()))
+        .await
+        .ok()
+        .and_then(|r| r.ok().and_then(|ready| ready.clone()));
+    ready.unwrap_or_else(|| {
+        tracing::warn!("⚠️ El cliente no entregó sus raíces, se usan las del servidor");
+        Arc::new(fallback())
+    })
+}
+
+/// Raíces del servidor: `[workspace] roots` o el directorio actual
+pub fn fallback() -> Vec<PathBuf> {
+    let configured: Vec<PathBuf> = CONFIG
+        .workspace
+        .roots
+        .iter()
+        .filter_map(|r| std::fs::canonicalize(r).ok())
+        .collect();
+    if !configured.is_empty() {
+        return configured;
+    }
+    std::env::current_dir()
+        .and_then(std::fs::canonicalize)
+        .map(|cwd| vec![cwd])
+        .unwrap_or_default()
+}
+
+/// Canonicaliza `path` y verifica que quede dentro de alguna raíz
+pub fn confine(path: &str, roots: &[PathBuf]) -> Result<PathBuf> {
+    let canonical = canonicalize(Path::new(path))?;
+    if roots.iter().any(|root| canonical.starts_with(root)) {
+        Ok(canonical)
+    } else {
+        Err(MemoryPError::PathNotAllowed(canonical))
+    }
+}
+
+/// Como `fs::canonicalize`, pero admite rutas que aún no existen: resuelve el ancestro
+/// existente más cercano y le agrega el resto (sin `..`, que podría escapar tras un symlink)
+fn canonicalize(path: &Path) -> Result<PathBuf> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    let mut existing = absolute.as_path();
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(canonical) = std::fs::canonicalize(existing) {
+            if rest.iter().any(|c| matches!(c, Component::ParentDir)) {
+                return Err(MemoryPError::PathNotAllowed(absolute.clone()));
+            }
+            return Ok(rest
+                .iter()
+                .rev()
+                .fold(canonical, |acc, c| acc.join(c.as_os_str())));
+        }
+        match (existing.parent(), existing.components().next_back()) {
+            (Some(parent), Some(last)) => {
+                rest.push(last);
+                existing = parent;
+            }
+            _ => return Err(MemoryPError::PathNotAllowed(absolute.clone())),
+        }
+    }
+}
+
+/// `{"roots": [{"uri": "file:///..."}]}` → rutas canonicalizadas (las que no existen se omiten)
+fn parse(result: &Value) -> Vec<PathBuf> {
+    result
+        .get("roots")
+        .and_then(|r| r.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|root| root.get("uri").and_then(|u| u.as_str()))
+        .filter_map(|uri| {
+            let path = decode_component(uri.strip_prefix("file://")?).ok()?;
+            // file:///C:/proyecto → C:/proyecto
+            let path = match path.as_bytes() {
+                [b'/', _, b':', ..] => path[1..].to_string(),
+                _ => path,
+            };
+            match std::fs::canonicalize(&path) {
+                Ok(canonical) => Some(canonical),
+                Err(e) => {
+                    tracing::warn!("⚠️ Raíz inválida {}: {}", uri, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_confine_to_roots() {
+        let src = std::fs::canonicalize("src").unwrap();
+        let roots = parse(&json!({ "roots": [{ "uri": format!("file://{}", src.display()) }] }));
+        assert_eq!(roots, vec![src.clone()]);
+
+        assert_eq!(confine("src/main.rs", &roots).unwrap(), src.join("main.rs"));
+        assert_eq!(
+            confine("src/nuevo.rs", &roots).unwrap(),
+            src.join("nuevo.rs")
+        );
+        assert!(confine("Cargo.toml", &roots).is_err());
+        assert!(confine("src/../Cargo.toml", &roots).is_err());
+        assert!(confine("src/no/existe/../../../Cargo.toml", &roots).is_err());
+    }
+}
@@ -0,0 +1,201 @@
+//! schema.rs - Validación de `arguments` contra el `inputSchema` de cada tool
+//! Cubre el subconjunto de JSON Schema que usan nuestras tools: `type`, `enum`, `properties`,
+//! `required`, `additionalProperties`, `items`, `minItems`, `minimum`, `maximum`, `pattern` y
+//! `default`.
+//! Los defaults se escriben en los argumentos antes de despachar la tool.
+
+use crate::error::{MemoryPError, Result};
+use serde_json::Value;
+
+/// Valida `value` contra `schema` aplicando defaults; todas las violaciones juntas en un error
+pub fn validate(schema: &Value, value: &mut Value) -> Result<()> {
+    let mut violations = Vec::new();
+    check(schema, value, "", &mut violations);
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(MemoryPError::SchemaViolations(violations))
+    }
+}
+
+fn check(schema: &Value, value: &mut Value, pointer: &str, out: &mut Vec<(String, String)>) {
+    if let Some(expected) = schema.get("type") {
+        if !matches_type(expected, value) {
+            out.push((
+                pointer.to_string(),
+                format!("se esperaba {}, llegó {}", expected, type_name(value)),
+            ));
+            // Con el tipo equivocado el resto de reglas solo agregaría ruido
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(|e| e.as_array()) {
+        if !allowed.contains(value) {
+            out.push((
+                pointer.to_string(),
+                format!("{} no es uno de {}", value, Value::Array(allowed.clone())),
+            ));
+        }
+    }
+
+    let pattern = schema.get("pattern").and_then(|p| p.as_str());
+    if let (Some(pattern), Some(text)) = (pattern, value.as_str()) {
+        // Un patrón inválido es un error de la tool, no del cliente: no deja pasar nada
+        if !regex::Regex::new(pattern).is_ok_and(|re| re.is_match(text)) {
+            out.push((
+                pointer.to_string(),
+                format!("no cumple el patrón {}", pattern),
+            ));
+        }
+    }
+
+    if let Some(n) = value.as_f64() {
+        if let Some(min) = schema.get("minimum").and_then(|m| m.as_f64()) {
+            if n < min {
+                out.push((pointer.to_string(), format!("debe ser >= {}", min)));
+            }
+        }
+        if let Some(max) = schema.get("maximum").and_then(|m| m.as_f64()) {
+            if n > max {
+                out.push((pointer.to_string(), format!("debe ser <= {}", max)));
+            }
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            let properties = schema.get("properties").and_then(|p| p.as_object());
+            if let Some(properties) = properties {
+                for (key, prop) in properties {
+                    if !map.contains_key(key) {
+                        if let Some(default) = prop.get("default") {
+                            map.insert(key.clone(), default.clone());
+                        }
+                    }
+                }
+            }
+            if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+                for key in required.iter().filter_map(|k| k.as_str()) {
+                    if !map.contains_key(key) {
+                        out.push((child(pointer, key), "falta la propiedad obligatoria".into()));
+                    }
+                }
+            }
+            let additional = schema.get("additionalProperties");
+            for (key, item) in map.iter_mut() {
+                match properties.and_then(|p| p.get(key)) {
+                    Some(prop) => check(prop, item, &child(pointer, key), out),
+                    None => match additional {
+                        Some(Value::Bool(false)) => {
+                            out.push((child(pointer, key), "propiedad no permitida".into()))
+                        }
+                        Some(extra @ Value::Object(_)) => {
+                            check(extra, item, &child(pointer, key), out)
+                        }
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(|m| m.as_u64()) {
+                if (items.len() as u64) < min {
+                    out.push((
+                        pointer.to_string(),
+                        format!("se esperaban al menos {} elementos", min),
+                    ));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter_mut().enumerate() {
+                    check(item_schema, item, &child(pointer, &i.to_string()), out);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn matches_type(expected: &Value, value: &Value) -> bool {
+    match expected {
+        Value::String(t) => is_type(t, value),
+        Value::Array(types) => types
+            .iter()
+            .filter_map(|t| t.as_str())
+            .any(|t| is_type(t, value)),
+        _ => true,
+    }
+}
+
+fn is_type(expected: &str, value: &Value) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Agrega un segmento a un JSON Pointer (RFC 6901: `~` → `~0`, `/` → `~1`)
+fn child(pointer: &str, segment: &str) -> String {
+    format!(
+        "{}/{}",
+        pointer,
+        segment.replace('~', "~0").replace('/', "~1")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_reports_pointers_and_applies_defaults() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "mode": { "type": "string", "enum": ["deep", "quick"], "default": "deep" },
+                "steps": { "type": "array", "items": { "type": "integer" } }
+            },
+            "required": ["path"],
+            "additionalProperties": false
+        });
+
+        let mut args = json!({ "path": "/src" });
+        validate(&schema, &mut args).unwrap();
+        assert_eq!(args["mode"], "deep");
+
+        let mut args = json!({ "pth": "/src", "mode": "slow", "steps": [1, "x"] });
+        let Err(MemoryPError::SchemaViolations(violations)) = validate(&schema, &mut args) else {
+            panic!("se esperaban violaciones");
+        };
+        let pointers: Vec<&str> = violations.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(pointers, ["/path", "/mode", "/pth", "/steps/1"]);
+    }
+
+    #[test]
+    fn test_pattern_rejects_non_matching_strings() {
+        let schema = json!({ "type": "string", "pattern": "^[A-Za-z0-9_-]+$" });
+        assert!(validate(&schema, &mut json!("sim_1")).is_ok());
+        assert!(validate(&schema, &mut json!("../../etc/foo")).is_err());
+    }
+}
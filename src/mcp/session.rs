@@ -0,0 +1,355 @@
+//! session.rs - Sesiones del transporte Streamable HTTP (MCP 2025-03-26)
+//! Cada sesión nace con `initialize`, se identifica con `Mcp-Session-Id` y guarda un
+//! historial acotado de eventos SSE para poder reanudar streams con `Last-Event-ID`.
+
+use crate::error::{MemoryPError, Result};
+use crate::mcp::{logging, notifications};
+use lazy_static::lazy_static;
+use serde_json::{json, Map, Value};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
+
+pub const SESSION_HEADER: &str = "mcp-session-id";
+pub const LAST_EVENT_ID_HEADER: &str = "last-event-id";
+
+/// Stream GET de mensajes iniciados por el servidor; los POST usan ids > 0
+pub const GET_STREAM: u64 = 0;
+
+/// Eventos retenidos por sesión para reanudación
+const HISTORY_LIMIT: usize = 1024;
+/// Sesiones sin actividad durante este tiempo se descartan
+const SESSION_IDLE_TTL: Duration = Duration::from_secs(60 * 60);
+const REAPER_INTERVAL: Duration = Duration::from_secs(60);
+
+lazy_static! {
+    static ref SESSIONS: scc::HashMap<String, Arc<Session>> = scc::HashMap::new();
+}
+
+/// Respuesta del cliente a un request del servidor: `result` o `error`
+type ClientReply = std::result::Result<Value, Value>;
+
+/// Evento SSE emitido en algún stream de la sesión
+#[derive(Debug, Clone)]
+pub struct SessionEvent {
+    pub id: u64,
+    pub stream: u64,
+    pub message: Value,
+}
+
+pub struct Session {
+    pub id: String,
+    /// Versión del protocolo negociada en `initialize`
+    protocol_version: Mutex<Option<String>>,
+    /// Nivel mínimo de `notifications/message` (logging/setLevel)
+    log_level: AtomicU8,
+    /// `capabilities` que el cliente declaró en `initialize`
+    client_capabilities: Mutex<Value>,
+    /// Raíces del cliente (`roots/list`) canonicalizadas; `None` mientras no llegan
+    roots: watch::Sender<Option<Arc<Vec<PathBuf>>>>,
+    /// Canal directo al cliente (stdio); sin él, los requests salen por el stream GET
+    direct: Mutex<Option<mpsc::WeakUnboundedSender<Value>>>,
+    next_request_id: AtomicU64,
+    /// Requests del servidor al cliente esperando respuesta
+    pending: scc::HashMap<String, oneshot::Sender<ClientReply>>,
+    next_event_id: AtomicU64,
+    next_stream_id: AtomicU64,
+    last_seen: AtomicU64,
+    history: Mutex<VecDeque<SessionEvent>>,
+    live: broadcast::Sender<SessionEvent>,
+    closed: watch::Sender<bool>,
+}
+
+impl Session {
+    fn new() -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            protocol_version: Mutex::new(None),
+            log_level: AtomicU8::new(logging::DEFAULT_LEVEL),
+            client_capabilities: Mutex::new(Value::Null),
+            roots: watch::channel(None).0,
+            direct: Mutex::new(None),
+            next_request_id: AtomicU64::new(1),
+            pending: scc::HashMap::new(),
+            next_event_id: AtomicU64::new(1),
+            next_stream_id: AtomicU64::new(GET_STREAM + 1),
+            last_seen: AtomicU64::new(now_secs()),
+            history: Mutex::new(VecDeque::new()),
+            live: broadcast::channel(256).0,
+            closed: watch::channel(false).0,
+        }
+    }
+
+    /// Sesión fuera del registro HTTP, para transportes de un solo cliente (stdio)
+    pub fn standalone() -> Arc<Self> {
+        Arc::new(Self::new())
+    }
+
+    pub fn protocol_version(&self) -> Option<String> {
+        self.protocol_version.lock().ok()?.clone()
+    }
+
+    pub fn set_protocol_version(&self, version: &str) {
+        if let Ok(mut v) = self.protocol_version.lock() {
+            *v = Some(version.to_string());
+        }
+    }
+
+    pub fn log_level(&self) -> u8 {
+        self.log_level.load(Ordering::Relaxed)
+    }
+
+    pub fn set_log_level(&self, level: u8) {
+        logging::note_level(level);
+        self.log_level.store(level, Ordering::Relaxed);
+    }
+
+    pub fn set_client_capabilities(&self, capabilities: Value) {
+        if let Ok(mut caps) = self.client_capabilities.lock() {
+            *caps = capabilities;
+        }
+    }
+
+    /// ¿Declaró el cliente esta capacidad (`roots`, `sampling`...)?
+    pub fn client_supports(&self, capability: &str) -> bool {
+        self.client_capabilities
+            .lock()
+            .is_ok_and(|caps| caps.get(capability).is_some_and(|c| !c.is_null()))
+    }
+
+    pub fn roots(&self) -> watch::Receiver<Option<Arc<Vec<PathBuf>>>> {
+        self.roots.subscribe()
+    }
+
+    /// `None` marca las raíces como pendientes (se está pidiendo la lista nueva)
+    pub fn set_roots(&self, roots: Option<Vec<PathBuf>>) {
+        self.roots.send_replace(roots.map(Arc::new));
+    }
+
+    /// Transportes con canal propio (stdio) reciben los requests del servidor por ahí
+    pub fn attach(&self, direct: mpsc::WeakUnboundedSender<Value>) {
+        if let Ok(mut d) = self.direct.lock() {
+            *d = Some(direct);
+        }
+    }
+
+    /// Request del servidor al cliente (`roots/list`...); espera su respuesta hasta `timeout`
+    pub async fn request(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        timeout: Duration,
+    ) -> Result<Value> {
+        let id = format!(
+            "srv-{}",
+            self.next_request_id.fetch_add(1, Ordering::Relaxed)
+        );
+        let (tx, rx) = oneshot::channel();
+        let _ = self.pending.insert(id.clone(), tx);
+
+        let mut message = json!({ "jsonrpc": "2.0", "id": id, "method": method });
+        if let Some(params) = params {
+            message["params"] = params;
+        }
+        self.send(message);
+
+        let outcome = tokio::time::timeout(timeout, rx).await;
+        self.pending.remove(&id);
+        match outcome {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(error))) => Err(MemoryPError::Other(format!(
+                "El cliente rechazó {}: {}",
+                method, error
+            ))),
+            _ => Err(MemoryPError::Other(format!(
+                "El cliente no respondió {} en {:?}",
+                method, timeout
+            ))),
+        }
+    }
+
+    /// Mensaje del servidor solo para este cliente: por el canal directo o el stream GET
+    pub fn send(&self, message: Value) {
+        let direct = self
+            .direct
+            .lock()
+            .ok()
+            .and_then(|d| d.as_ref().and_then(|d| d.upgrade()));
+        match direct {
+            Some(direct) => {
+                let _ = direct.send(message);
+            }
+            None => self.push(message),
+        }
+    }
+
+    /// Entrega la respuesta del cliente al request pendiente; `false` si nadie la esperaba
+    pub fn resolve(&self, response: &Map<String, Value>) -> bool {
+        let Some(id) = response.get("id").and_then(|v| v.as_str()) else {
+            return false;
+        };
+        let Some((_, waiter)) = self.pending.remove(id) else {
+            return false;
+        };
+        let reply = match response.get("error") {
+            Some(error) => Err(error.clone()),
+            None => Ok(response.get("result").cloned().unwrap_or(Value::Null)),
+        };
+        waiter.send(reply).is_ok()
+    }
+
+    /// Reserva un id para un stream SSE nuevo (respuesta a un POST)
+    pub fn open_stream(&self) -> u64 {
+        self.next_stream_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Registra un mensaje en un stream, asignándole un id de evento reanudable
+    pub fn record(&self, stream: u64, message: Value) -> SessionEvent {
+        let event = SessionEvent {
+            id: self.next_event_id.fetch_add(1, Ordering::Relaxed),
+            stream,
+            message,
+        };
+        if let Ok(mut history) = self.history.lock() {
+            if history.len() >= HISTORY_LIMIT {
+                history.pop_front();
+            }
+            history.push_back(event.clone());
+        }
+        event
+    }
+
+    /// Envía un mensaje iniciado por el servidor a los streams GET de la sesión
+    pub fn push(&self, message: Value) {
+        let event = self.record(GET_STREAM, message);
+        let _ = self.live.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SessionEvent> {
+        self.live.subscribe()
+    }
+
+    /// Eventos posteriores a `last_event_id` en el mismo stream en que se emitió
+    pub fn replay_after(&self, last_event_id: u64) -> Vec<SessionEvent> {
+        let Ok(history) = self.history.lock() else {
+            return Vec::new();
+        };
+        let Some(stream) = history
+            .iter()
+            .find(|e| e.id == last_event_id)
+            .map(|e| e.stream)
+        else {
+            return Vec::new();
+        };
+        history
+            .iter()
+            .filter(|e| e.stream == stream && e.id > last_event_id)
+            .cloned()
+            .collect()
+    }
+
+    pub fn touch(&self) {
+        self.last_seen.store(now_secs(), Ordering::Relaxed);
+    }
+
+    pub fn closed(&self) -> watch::Receiver<bool> {
+        self.closed.subscribe()
+    }
+}
+
+/// Crea y registra una sesión; reenvía el bus global de notificaciones a su stream GET
+pub fn create() -> Arc<Session> {
+    let session = Arc::new(Session::new());
+    let _ = SESSIONS.insert(session.id.clone(), session.clone());
+
+    let forward = session.clone();
+    let mut closed = session.closed();
+    tokio::spawn(async move {
+        let mut events = notifications::subscribe();
+        loop {
+            tokio::select! {
+                msg = notifications::next(&mut events) => match msg {
+                    Some(msg) if logging::should_forward(&msg, forward.log_level()) => forward.push(msg),
+                    Some(_) => {}
+                    None => break,
+                },
+                _ = closed.changed() => break,
+            }
+        }
+    });
+
+    tracing::info!("🔑 Sesión MCP creada: {}", session.id);
+    session
+}
+
+/// Sesiones HTTP abiertas
+pub fn count() -> usize {
+    SESSIONS.len()
+}
+
+pub fn get(id: &str) -> Option<Arc<Session>> {
+    let session = SESSIONS.read(id, |_, s| s.clone())?;
+    session.touch();
+    Some(session)
+}
+
+/// Termina una sesión; los streams abiertos se cierran
+pub fn remove(id: &str) -> bool {
+    match SESSIONS.remove(id) {
+        Some((_, session)) => {
+            let _ = session.closed.send(true);
+            tracing::info!("🔒 Sesión MCP cerrada: {}", id);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Descarta periódicamente las sesiones abandonadas sin DELETE
+pub fn spawn_reaper() {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(REAPER_INTERVAL);
+        loop {
+            interval.tick().await;
+            let cutoff = now_secs().saturating_sub(SESSION_IDLE_TTL.as_secs());
+            let mut expired = Vec::new();
+            SESSIONS.scan(|id, s| {
+                if s.last_seen.load(Ordering::Relaxed) < cutoff {
+                    expired.push(id.clone());
+                }
+            });
+            for id in expired {
+                remove(&id);
+            }
+        }
+    });
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_only_same_stream() {
+        let session = Session::new();
+        let post = session.open_stream();
+        let first = session.record(GET_STREAM, serde_json::json!(1));
+        session.record(post, serde_json::json!("post"));
+        session.record(GET_STREAM, serde_json::json!(2));
+
+        let replay = session.replay_after(first.id);
+        assert_eq!(replay.len(), 1);
+        assert_eq!(replay[0].message, serde_json::json!(2));
+        assert!(session.replay_after(999).is_empty());
+    }
+}
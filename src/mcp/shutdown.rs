@@ -0,0 +1,134 @@
+//! shutdown.rs - Apagado ordenado con Ctrl+C o SIGTERM
+//! La primera señal deja de aceptar conexiones y trabajo nuevo (`tools/call` y `POST /jobs`
+//! responden SERVER_BUSY), cierra los streams SSE abiertos y espera a las tools y jobs en curso.
+//! Si siguen tras `[shutdown] drain_timeout_secs` se cancelan: el motor termina el archivo que
+//! está escribiendo (las escrituras son atómicas, ver `workspace::write_atomic`) y no abre más.
+//! Una segunda señal sale al instante. Código de salida: 0 si todo terminó por su cuenta,
+//! 128 + señal (130 Ctrl+C, 143 SIGTERM) si hubo que cancelar o forzar la salida.
+
+use crate::config::CONFIG;
+use crate::mcp::{cancellation, jobs};
+use lazy_static::lazy_static;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Margen tras cancelar para que cada tool cierre el archivo en curso
+const CANCEL_GRACE: Duration = Duration::from_secs(10);
+/// Cada cuánto se mira si ya no queda trabajo en curso
+const DRAIN_POLL: Duration = Duration::from_millis(100);
+
+const SIGINT: u8 = 2;
+const SIGTERM: u8 = 15;
+
+lazy_static! {
+    static ref STOP: watch::Sender<bool> = watch::Sender::new(false);
+}
+
+/// Señal que pidió el apagado (0 = fin de stdin u otro motivo interno)
+static SIGNAL: AtomicU8 = AtomicU8::new(0);
+/// Hubo que cancelar trabajo en curso
+static FORCED: AtomicBool = AtomicBool::new(false);
+
+/// Escucha Ctrl+C y SIGTERM: la primera señal inicia el apagado, la segunda sale al instante
+pub fn spawn_listener() {
+    tokio::spawn(async {
+        loop {
+            let signal = next_signal().await;
+            if is_shutting_down() {
+                tracing::warn!("⚠️ Segunda señal: salida inmediata");
+                std::process::exit(128 + signal as i32);
+            }
+            SIGNAL.store(signal, Ordering::Relaxed);
+            trigger(if signal == SIGTERM { "SIGTERM" } else { "Ctrl+C" });
+        }
+    });
+}
+
+#[cfg(unix)]
+async fn next_signal() -> u8 {
+    use tokio::signal::unix::{signal, SignalKind};
+    match signal(SignalKind::terminate()) {
+        Ok(mut terminate) => tokio::select! {
+            _ = tokio::signal::ctrl_c() => SIGINT,
+            _ = terminate.recv() => SIGTERM,
+        },
+        Err(_) => {
+            let _ = tokio::signal::ctrl_c().await;
+            SIGINT
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn next_signal() -> u8 {
+    let _ = tokio::signal::ctrl_c().await;
+    SIGINT
+}
+
+/// Inicia el apagado (una sola vez) y arma el plazo de drenado
+pub fn trigger(reason: &str) {
+    if STOP.send_replace(true) {
+        return;
+    }
+    tracing::info!(
+        "🛑 Apagando ({}): sin trabajo nuevo; {} tools y {} jobs en curso",
+        reason,
+        cancellation::active(),
+        jobs::counts().0
+    );
+    tokio::spawn(watchdog());
+}
+
+pub fn is_shutting_down() -> bool {
+    *STOP.borrow()
+}
+
+/// Se completa al pedirse el apagado (para `with_graceful_shutdown` y los streams SSE)
+pub async fn requested() {
+    let mut rx = STOP.subscribe();
+    let _ = rx.wait_for(|stop| *stop).await;
+}
+
+/// Espera a que no quede ninguna tool ni job en curso
+pub async fn drain() {
+    while cancellation::active() > 0 || jobs::counts().0 > 0 {
+        tokio::time::sleep(DRAIN_POLL).await;
+    }
+}
+
+/// Vencido el plazo cancela lo que siga en curso; si ni así termina, sale a la fuerza.
+/// Un apagado que termina antes no llega a despertarlo: el runtime se va con él.
+async fn watchdog() {
+    tokio::time::sleep(Duration::from_secs(CONFIG.shutdown.drain_timeout_secs)).await;
+    FORCED.store(true, Ordering::Relaxed);
+    tracing::warn!(
+        "⏱️ Plazo de {}s vencido: se cancelan {} tools y {} jobs",
+        CONFIG.shutdown.drain_timeout_secs,
+        cancellation::cancel_all(),
+        jobs::cancel_all()
+    );
+    tokio::time::sleep(CANCEL_GRACE).await;
+    tracing::error!("❌ El apagado no terminó tras cancelar: salida forzada");
+    std::process::exit(exit_status() as i32);
+}
+
+/// 128 + señal, o 1 si el apagado no vino de una señal
+fn exit_status() -> u8 {
+    match SIGNAL.load(Ordering::Relaxed) {
+        0 => 1,
+        signal => 128 + signal,
+    }
+}
+
+/// Sale tras un apagado ya drenado. Sin esperar al runtime: la lectura de stdin ocupa un hilo
+/// bloqueante que no se puede interrumpir y el runtime lo esperaría para siempre.
+pub fn exit() -> ! {
+    let status = if FORCED.load(Ordering::Relaxed) {
+        exit_status()
+    } else {
+        0
+    };
+    tracing::info!("👋 Apagado completo (código {})", status);
+    std::process::exit(status as i32)
+}
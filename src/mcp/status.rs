@@ -0,0 +1,63 @@
+//! status.rs - Estado del servidor para `/status` y `/health`
+//! Uptime, configuración efectiva, pools, caché de análisis, tools en curso y último error.
+
+use crate::config::CONFIG;
+use crate::error::MemoryPError;
+use crate::mcp::{admission, cancellation, jobs, metrics, session};
+use lazy_static::lazy_static;
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+lazy_static! {
+    static ref STARTED: Instant = Instant::now();
+    static ref LAST_ERROR: Mutex<Option<Value>> = Mutex::new(None);
+}
+
+/// Fija el instante de arranque (si no, cuenta desde la primera consulta)
+pub fn mark_started() {
+    lazy_static::initialize(&STARTED);
+}
+
+/// Recuerda el último error de un request o de una tool (`origin` = método o tool)
+pub fn record_error(origin: &str, err: &MemoryPError) {
+    metrics::record_error(origin, err);
+    let at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    if let Ok(mut last) = LAST_ERROR.lock() {
+        *last = Some(json!({
+            "origin": origin,
+            "kind": err.kind(),
+            "code": err.rpc_code(),
+            "message": err.to_string(),
+            "at": at,
+        }));
+    }
+}
+
+pub fn uptime_secs() -> u64 {
+    STARTED.elapsed().as_secs()
+}
+
+pub fn snapshot() -> Value {
+    let (jobs_active, jobs_finished) = jobs::counts();
+    json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION"),
+        "uptime_secs": uptime_secs(),
+        "config": *CONFIG,
+        "threads": {
+            "configured": CONFIG.parallelism.threads,
+            "rayon_global": rayon::current_num_threads(),
+            "cpus": num_cpus::get(),
+        },
+        "analysis_cache_entries": crate::analyzer::CodeAnalyzer::cache_entries(),
+        "active_jobs": cancellation::active(),
+        "admission": admission::snapshot(),
+        "async_jobs": { "active": jobs_active, "finished": jobs_finished },
+        "sessions": session::count(),
+        "last_error": LAST_ERROR.lock().ok().and_then(|e| e.clone()),
+    })
+}
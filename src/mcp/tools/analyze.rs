@@ -0,0 +1,132 @@
+//! analyze.rs - Tool `analyze`: análisis paralelo (deep/quick) u overview de un proyecto
+
+use super::{
+    blocking, cancelled_note, results_schema, stats_schema, tool_result, McpTool, ToolContext,
+};
+use crate::analyzer::CodeAnalyzer;
+use crate::error::Result;
+use crate::mcp::resources;
+use crate::parallel_engine::{self, ParallelConfig};
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+
+pub struct AnalyzeTool;
+
+impl McpTool for AnalyzeTool {
+    fn name(&self) -> &str {
+        "analyze"
+    }
+
+    fn description(&self) -> &str {
+        "🔬 Análisis masivo paralelo con métricas, seguridad y overview arquitectónico."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Ruta al proyecto" },
+                "mode": { "type": "string", "enum": ["deep", "quick", "overview"], "default": "deep", "description": "deep=completo, quick=rápido, overview=arquitectura" },
+                "extension": { "type": "string", "default": "rs" },
+                "use_gitignore": { "type": "boolean", "default": true },
+                "include_hidden": { "type": "boolean", "default": false }
+            },
+            "required": ["path"],
+            "additionalProperties": false
+        })
+    }
+
+    fn output_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "mode": { "type": "string" },
+                "path": { "type": "string" },
+                "files": { "type": "integer", "description": "Solo mode=overview" },
+                "has_cargo": { "type": "boolean", "description": "Solo mode=overview" },
+                "stats": stats_schema(),
+                "results": results_schema()
+            },
+            "required": ["mode", "path"]
+        }))
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({
+            "title": "Analizar proyecto",
+            "readOnlyHint": true,
+            "destructiveHint": false,
+            "idempotentHint": true,
+            "openWorldHint": false
+        }))
+    }
+
+    fn call(&self, arguments: Value, ctx: ToolContext) -> BoxFuture<'static, Result<Value>> {
+        blocking(move || run(&arguments, ctx))
+    }
+}
+
+fn run(arguments: &Value, ctx: ToolContext) -> Result<Value> {
+    let path = &ctx.confine(
+        arguments
+            .get("path")
+            .and_then(|v| v.as_str())
+            .unwrap_or("."),
+    )?;
+    let mode = arguments
+        .get("mode")
+        .and_then(|v| v.as_str())
+        .unwrap_or("deep");
+    let ext = arguments
+        .get("extension")
+        .and_then(|v| v.as_str())
+        .unwrap_or("rs");
+    let use_gitignore = arguments
+        .get("use_gitignore")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let include_hidden = arguments
+        .get("include_hidden")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let files = CodeAnalyzer::scan_files(path, ext, use_gitignore, include_hidden)?;
+    match mode {
+        "overview" => {
+            let has_cargo = std::path::Path::new(path).join("Cargo.toml").exists();
+            Ok(tool_result(
+                format!(
+                    "🏛️ Overview: {} | Files: {} | Cargo.toml: {}",
+                    path,
+                    files.len(),
+                    if has_cargo { "✅" } else { "❌" }
+                ),
+                json!({
+                    "mode": mode,
+                    "path": path,
+                    "files": files.len(),
+                    "has_cargo": has_cargo
+                }),
+            ))
+        }
+        _ => {
+            let config = ParallelConfig {
+                monitor: Some(ctx.monitor),
+                ..Default::default()
+            };
+            let (res, stats) = parallel_engine::ultra_analyze(&files, config)?;
+            resources::record_analysis(path, mode, ext, &res, &stats);
+            Ok(tool_result(
+                format!(
+                    "🔬 Analyze [{}] en {}ms. Archivos: {} (exitosos: {}){}",
+                    mode,
+                    stats.total_duration_ms,
+                    stats.total_files,
+                    stats.successful,
+                    cancelled_note(stats.cancelled)
+                ),
+                json!({ "mode": mode, "path": path, "stats": stats, "results": res }),
+            ))
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! delete.rs - Tool `delete`: borrado paralelo de archivos y directorios
+
+use super::{
+    blocking, cancelled_note, results_schema, stats_schema, tool_result, McpTool, ToolContext,
+};
+use crate::error::Result;
+use crate::mcp::auth::Scope;
+use crate::parallel_engine::{self, ParallelConfig};
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+pub struct DeleteTool;
+
+impl McpTool for DeleteTool {
+    fn name(&self) -> &str {
+        "delete"
+    }
+
+    fn description(&self) -> &str {
+        "🗑️ Borrado masivo paralelo de archivos y directorios (recursivo)."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "paths": { "type": "array", "items": { "type": "string" }, "minItems": 1, "description": "Archivos o directorios a eliminar" },
+                "dry_run": { "type": "boolean", "default": true }
+            },
+            "required": ["paths"],
+            "additionalProperties": false
+        })
+    }
+
+    fn output_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "dry_run": { "type": "boolean" },
+                "stats": stats_schema(),
+                "results": results_schema()
+            },
+            "required": ["dry_run", "stats", "results"]
+        }))
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({
+            "title": "Eliminar archivos",
+            "readOnlyHint": false,
+            "destructiveHint": true,
+            "idempotentHint": true,
+            "openWorldHint": false
+        }))
+    }
+
+    fn scope(&self) -> Scope {
+        Scope::Delete
+    }
+
+    fn call(&self, arguments: Value, ctx: ToolContext) -> BoxFuture<'static, Result<Value>> {
+        blocking(move || run(&arguments, ctx))
+    }
+}
+
+fn run(arguments: &Value, ctx: ToolContext) -> Result<Value> {
+    let dry_run = arguments
+        .get("dry_run")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let paths = arguments
+        .get("paths")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|p| p.as_str())
+        .map(|p| ctx.confine(p).map(PathBuf::from))
+        .collect::<Result<Vec<PathBuf>>>()?;
+    let config = ParallelConfig {
+        monitor: Some(ctx.monitor),
+        ..Default::default()
+    };
+    let (res, stats) = parallel_engine::ultra_delete(&paths, config, dry_run)?;
+    Ok(tool_result(
+        format!(
+            "🗑️ Delete {} en {}ms. Archivos: {} (eliminados: {}){}",
+            if dry_run { "[DRY]" } else { "[REAL]" },
+            stats.total_duration_ms,
+            stats.total_files,
+            stats.successful,
+            cancelled_note(stats.cancelled)
+        ),
+        json!({ "dry_run": dry_run, "stats": stats, "results": res }),
+    ))
+}
@@ -0,0 +1,119 @@
+//! edit.rs - Tool `edit`: edición masiva atómica (replace/regex/append)
+
+use super::{
+    blocking, cancelled_note, results_schema, stats_schema, tool_result, McpTool, ToolContext,
+};
+use crate::error::Result;
+use crate::mcp::auth::Scope;
+use crate::mcp::models::UltraEditRequest;
+use crate::parallel_engine;
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+
+pub struct EditTool;
+
+impl McpTool for EditTool {
+    fn name(&self) -> &str {
+        "edit"
+    }
+
+    fn description(&self) -> &str {
+        "✏️ Edición masiva atómica: replace, regex, append."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "mode": { "type": "string", "enum": ["replace", "regex", "append"], "description": "Tipo de operación" },
+                "changes": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "path": { "type": "string" },
+                            "operations": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "type": { "type": "string", "enum": ["Replace", "RegexReplace", "Append"] },
+                                        "data": { "type": "object" }
+                                    },
+                                    "required": ["type", "data"],
+                                    "additionalProperties": false
+                                }
+                            }
+                        },
+                        "required": ["path", "operations"],
+                        "additionalProperties": false
+                    }
+                },
+                "dry_run": { "type": "boolean", "default": true }
+            },
+            "required": ["mode", "changes"],
+            "additionalProperties": false
+        })
+    }
+
+    fn output_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "mode": { "type": "string" },
+                "dry_run": { "type": "boolean" },
+                "stats": stats_schema(),
+                "results": results_schema()
+            },
+            "required": ["mode", "dry_run", "stats", "results"]
+        }))
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({
+            "title": "Editar archivos",
+            "readOnlyHint": false,
+            "destructiveHint": true,
+            "idempotentHint": false,
+            "openWorldHint": false
+        }))
+    }
+
+    fn scope(&self) -> Scope {
+        Scope::Edit
+    }
+
+    fn call(&self, arguments: Value, ctx: ToolContext) -> BoxFuture<'static, Result<Value>> {
+        blocking(move || run(&arguments, ctx))
+    }
+}
+
+fn run(arguments: &Value, ctx: ToolContext) -> Result<Value> {
+    let mode = arguments
+        .get("mode")
+        .and_then(|v| v.as_str())
+        .unwrap_or("replace");
+    let dry_run = arguments
+        .get("dry_run")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    let mut req = serde_json::from_value::<UltraEditRequest>(arguments.clone())?;
+    for change in &mut req.changes {
+        change.path = ctx.confine(&change.path)?;
+    }
+    let mut config = crate::config::AppConfig::load().to_parallel_config();
+    config.monitor = Some(ctx.monitor);
+    let (res, stats) = parallel_engine::ultra_edit(&req.changes, config, dry_run)?;
+    Ok(tool_result(
+        format!(
+            "✏️ Edit [{}] {} en {}ms. Archivos: {}{}",
+            mode,
+            if dry_run { "[DRY]" } else { "[APPLIED]" },
+            stats.total_duration_ms,
+            stats.total_files,
+            cancelled_note(stats.cancelled)
+        ),
+        json!({ "mode": mode, "dry_run": dry_run, "stats": stats, "results": res }),
+    ))
+}
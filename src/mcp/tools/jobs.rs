@@ -0,0 +1,157 @@
+//! jobs.rs - Tools `job_status` y `job_result`: trabajos asíncronos lanzados con `POST /jobs`
+
+use super::{tool_result, McpTool, ToolContext};
+use crate::error::{MemoryPError, Result};
+use crate::mcp::jobs::{self, Job};
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct JobStatusTool;
+
+pub struct JobResultTool;
+
+fn id_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "id": { "type": "string", "description": "Id devuelto por POST /jobs" }
+        },
+        "required": ["id"],
+        "additionalProperties": false
+    })
+}
+
+fn read_only(title: &str) -> Option<Value> {
+    Some(json!({
+        "title": title,
+        "readOnlyHint": true,
+        "destructiveHint": false,
+        "idempotentHint": true,
+        "openWorldHint": false
+    }))
+}
+
+/// Job pedido, si es del mismo token que llama
+fn job(arguments: &Value, ctx: &ToolContext) -> Result<Arc<Job>> {
+    let id = arguments
+        .get("id")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    jobs::get(id, ctx.grant.as_ref()).ok_or_else(|| {
+        MemoryPError::InvalidParams(format!("Job desconocido o ya descartado: {}", id))
+    })
+}
+
+impl McpTool for JobStatusTool {
+    fn name(&self) -> &str {
+        "job_status"
+    }
+
+    fn description(&self) -> &str {
+        "📋 Estado y progreso de un trabajo asíncrono (POST /jobs)."
+    }
+
+    fn input_schema(&self) -> Value {
+        id_schema()
+    }
+
+    fn output_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "tool": { "type": "string" },
+                "status": { "type": "string", "enum": ["queued", "running", "completed", "failed", "cancelled"] },
+                "progress": {
+                    "type": "object",
+                    "properties": {
+                        "done": { "type": "integer" },
+                        "total": { "type": "integer" }
+                    }
+                },
+                "created_at": { "type": "integer" },
+                "finished_at": { "type": ["integer", "null"] }
+            },
+            "required": ["id", "tool", "status", "progress"]
+        }))
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        read_only("Estado de un job")
+    }
+
+    fn heavy(&self) -> bool {
+        false
+    }
+
+    fn call(&self, arguments: Value, ctx: ToolContext) -> BoxFuture<'static, Result<Value>> {
+        Box::pin(async move {
+            let job = job(&arguments, &ctx)?;
+            let view = job.view(false);
+            Ok(tool_result(
+                format!(
+                    "📋 Job {} [{}]: {} ({}/{})",
+                    job.id,
+                    job.tool,
+                    view["status"].as_str().unwrap_or_default(),
+                    view["progress"]["done"],
+                    view["progress"]["total"]
+                ),
+                view,
+            ))
+        })
+    }
+}
+
+impl McpTool for JobResultTool {
+    fn name(&self) -> &str {
+        "job_result"
+    }
+
+    fn description(&self) -> &str {
+        "📦 Resultado de un trabajo asíncrono terminado: el mismo que devolvería tools/call."
+    }
+
+    fn input_schema(&self) -> Value {
+        id_schema()
+    }
+
+    fn output_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "description": "structuredContent de la tool que corrió el job"
+        }))
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        read_only("Resultado de un job")
+    }
+
+    fn heavy(&self) -> bool {
+        false
+    }
+
+    fn call(&self, arguments: Value, ctx: ToolContext) -> BoxFuture<'static, Result<Value>> {
+        Box::pin(async move {
+            let job = job(&arguments, &ctx)?;
+            match job.outcome() {
+                Some(Ok(result)) => Ok(result),
+                Some(Err(error)) => Err(MemoryPError::Other(format!(
+                    "El job {} falló: {}",
+                    job.id, error.message
+                ))),
+                None => {
+                    let view = job.view(false);
+                    Err(MemoryPError::Other(format!(
+                        "El job {} sigue en curso ({}, {}/{}); consulta job_status",
+                        job.id,
+                        view["status"].as_str().unwrap_or_default(),
+                        view["progress"]["done"],
+                        view["progress"]["total"]
+                    )))
+                }
+            }
+        })
+    }
+}
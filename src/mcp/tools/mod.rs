@@ -0,0 +1,319 @@
+//! tools/mod.rs - Registro de tools MCP
+//! Cada tool implementa `McpTool` en su propio archivo; el registro alimenta tanto
+//! `tools/list` como `tools/call`. Una tool propia se agrega con `register`, sin tocar
+//! `mcp_api.rs`; registrar en caliente emite `notifications/tools/list_changed`.
+
+mod analyze;
+mod delete;
+mod edit;
+mod jobs;
+mod repair;
+mod simulate;
+mod workflow;
+
+pub use workflow::{load as load_workflow, saved_workflows};
+
+use crate::error::{MemoryPError, Result};
+use crate::mcp::auth::{Grant, Scope};
+use crate::mcp::models::Tool;
+use crate::mcp::{notifications, roots};
+use crate::parallel_engine::JobMonitor;
+use futures::future::BoxFuture;
+use lazy_static::lazy_static;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+lazy_static! {
+    /// Tools en orden de registro (el mismo orden que ve `tools/list`)
+    static ref REGISTRY: RwLock<Vec<Arc<dyn McpTool>>> = RwLock::new(Vec::new());
+}
+
+/// Lo que una tool recibe del servidor además de sus argumentos
+#[derive(Clone)]
+pub struct ToolContext {
+    /// Progreso y cancelación del `tools/call` en curso
+    pub monitor: Arc<JobMonitor>,
+    /// Raíces del cliente (o del servidor) a las que se confinan las rutas
+    pub roots: Arc<Vec<PathBuf>>,
+    /// Token HTTP del que llama (`None` en stdio o sin autenticación)
+    pub grant: Option<Grant>,
+}
+
+impl ToolContext {
+    /// Ruta canónica de un argumento, o `PathNotAllowed` si cae fuera de las raíces
+    pub fn confine(&self, path: &str) -> Result<String> {
+        roots::confine(path, &self.roots).map(|p| p.to_string_lossy().into_owned())
+    }
+}
+
+pub trait McpTool: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn description(&self) -> &str;
+
+    fn input_schema(&self) -> Value;
+
+    /// Forma de `structuredContent`, si la tool lo devuelve
+    fn output_schema(&self) -> Option<Value> {
+        None
+    }
+
+    /// `readOnlyHint`, `destructiveHint`, `idempotentHint`, `openWorldHint`...
+    fn annotations(&self) -> Option<Value> {
+        None
+    }
+
+    /// Permiso que debe tener el token HTTP para llamarla
+    fn scope(&self) -> Scope {
+        Scope::Read
+    }
+
+    /// Pasa por la admisión de `[limits]`; las consultas livianas no esperan turno
+    fn heavy(&self) -> bool {
+        true
+    }
+
+    /// Qué cuentan las `notifications/progress` de esta tool
+    fn progress_unit(&self) -> &'static str {
+        "archivos"
+    }
+
+    /// Ejecuta la tool. Errores de argumentos (`InvalidParams`) se devuelven como error JSON-RPC;
+    /// el resto como resultado con `isError: true`.
+    fn call(&self, arguments: Value, ctx: ToolContext) -> BoxFuture<'static, Result<Value>>;
+
+    fn definition(&self) -> Tool {
+        Tool {
+            name: self.name().to_string(),
+            description: self.description().to_string(),
+            input_schema: self.input_schema(),
+            output_schema: self.output_schema(),
+            annotations: self.annotations(),
+        }
+    }
+}
+
+/// Tools incluidas en el servidor
+pub fn builtin() -> Vec<Arc<dyn McpTool>> {
+    vec![
+        Arc::new(analyze::AnalyzeTool),
+        Arc::new(repair::RepairTool),
+        Arc::new(edit::EditTool),
+        Arc::new(delete::DeleteTool),
+        Arc::new(workflow::WorkflowTool),
+        Arc::new(simulate::SimulateTool),
+        Arc::new(jobs::JobStatusTool),
+        Arc::new(jobs::JobResultTool),
+    ]
+}
+
+/// Registra las tools incluidas; se llama una vez al arrancar cualquier transporte
+pub fn install_builtin() {
+    for tool in builtin() {
+        register(tool);
+    }
+}
+
+/// Agrega (o reemplaza, si el nombre ya existe) una tool y avisa a los clientes
+pub fn register(tool: Arc<dyn McpTool>) {
+    let Ok(mut tools) = REGISTRY.write() else {
+        tracing::error!(
+            "❌ Registro de tools envenenado; no se registra {}",
+            tool.name()
+        );
+        return;
+    };
+    match tools.iter().position(|t| t.name() == tool.name()) {
+        Some(i) => tools[i] = tool,
+        None => tools.push(tool),
+    }
+    drop(tools);
+    notifications::publish("notifications/tools/list_changed", None);
+}
+
+/// Quita una tool del registro y avisa a los clientes; `false` si no estaba
+#[allow(dead_code)]
+pub fn unregister(name: &str) -> bool {
+    let Ok(mut tools) = REGISTRY.write() else {
+        return false;
+    };
+    let before = tools.len();
+    tools.retain(|t| t.name() != name);
+    let removed = tools.len() != before;
+    drop(tools);
+    if removed {
+        notifications::publish("notifications/tools/list_changed", None);
+    }
+    removed
+}
+
+/// Tool de test registrada mientras viva el guard; al soltarlo sale del registro
+#[cfg(test)]
+pub struct ScopedTool(String);
+
+#[cfg(test)]
+impl Drop for ScopedTool {
+    fn drop(&mut self) {
+        unregister(&self.0);
+    }
+}
+
+/// Los tests comparten el registro global: cada uno registra con un nombre propio y lo quita
+#[cfg(test)]
+pub fn register_scoped(tool: Arc<dyn McpTool>) -> ScopedTool {
+    let name = tool.name().to_string();
+    register(tool);
+    ScopedTool(name)
+}
+
+pub fn get(name: &str) -> Option<Arc<dyn McpTool>> {
+    REGISTRY
+        .read()
+        .ok()?
+        .iter()
+        .find(|t| t.name() == name)
+        .cloned()
+}
+
+pub fn definitions() -> Vec<Tool> {
+    REGISTRY
+        .read()
+        .map(|tools| tools.iter().map(|t| t.definition()).collect())
+        .unwrap_or_default()
+}
+
+/// Corre trabajo bloqueante (rayon, disco) fuera del runtime async
+pub fn blocking<F>(work: F) -> BoxFuture<'static, Result<Value>>
+where
+    F: FnOnce() -> Result<Value> + Send + 'static,
+{
+    Box::pin(async move {
+        tokio::task::spawn_blocking(work)
+            .await
+            .map_err(|e| MemoryPError::Other(format!("La tool abortó: {}", e)))?
+    })
+}
+
+/// Sufijo para los resúmenes de trabajos cortados por `notifications/cancelled`
+pub fn cancelled_note(cancelled: bool) -> &'static str {
+    if cancelled {
+        " ⛔ CANCELADO: resultado parcial"
+    } else {
+        ""
+    }
+}
+
+pub fn text_result(text: String) -> Value {
+    json!({ "content": [{ "type": "text", "text": text }] })
+}
+
+/// Resumen legible en `content` y el detalle completo (según `outputSchema`) en `structuredContent`
+pub fn tool_result(summary: String, structured: Value) -> Value {
+    let mut result = text_result(summary);
+    result["structuredContent"] = structured;
+    result
+}
+
+/// `ProcessingStats` tal como se serializa en `structuredContent`
+pub fn stats_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "total_files": { "type": "integer" },
+            "successful": { "type": "integer" },
+            "errors": { "type": "integer" },
+            "warnings": { "type": "integer" },
+            "skipped": { "type": "integer" },
+            "total_bytes": { "type": "integer" },
+            "total_duration_ms": { "type": "integer" },
+            "cancelled": { "type": "boolean" }
+        }
+    })
+}
+
+/// `Vec<ProcessingResult>`: un elemento por archivo (o paso del pipeline) con sus hallazgos
+pub fn results_schema() -> Value {
+    json!({
+        "type": "array",
+        "items": {
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "status": { "type": "string", "enum": ["success", "warning", "error", "skipped"] },
+                "findings": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["path", "status", "findings"]
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_tools_declare_schemas_and_annotations() {
+        for tool in builtin() {
+            let value = serde_json::to_value(tool.definition()).unwrap();
+            assert_eq!(value["inputSchema"]["type"], "object", "{}", tool.name());
+            assert_eq!(
+                value["inputSchema"]["additionalProperties"],
+                false,
+                "{}",
+                tool.name()
+            );
+            assert_eq!(value["outputSchema"]["type"], "object", "{}", tool.name());
+            for hint in [
+                "readOnlyHint",
+                "destructiveHint",
+                "idempotentHint",
+                "openWorldHint",
+            ] {
+                assert!(
+                    value["annotations"][hint].is_boolean(),
+                    "{} {}",
+                    tool.name(),
+                    hint
+                );
+            }
+            // Solo las tools de lectura quedan al alcance de un token `read`
+            assert_eq!(
+                value["annotations"]["readOnlyHint"] == true,
+                tool.scope() == Scope::Read,
+                "{}",
+                tool.name()
+            );
+        }
+    }
+
+    struct NoopTool;
+
+    impl McpTool for NoopTool {
+        fn name(&self) -> &str {
+            "test_registry_noop"
+        }
+
+        fn description(&self) -> &str {
+            "no hace nada"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({ "type": "object" })
+        }
+
+        fn call(&self, _arguments: Value, _ctx: ToolContext) -> BoxFuture<'static, Result<Value>> {
+            Box::pin(async { Ok(text_result(String::new())) })
+        }
+    }
+
+    #[test]
+    fn test_scoped_tool_leaves_registry_on_drop() {
+        let listed = || definitions().iter().any(|t| t.name == "test_registry_noop");
+        let scoped = register_scoped(Arc::new(NoopTool));
+        assert!(listed() && get("test_registry_noop").is_some());
+        drop(scoped);
+        assert!(!listed() && get("test_registry_noop").is_none());
+        assert!(!unregister("test_registry_noop"));
+    }
+}
@@ -0,0 +1,102 @@
+//! repair.rs - Tool `repair`: reparación paralela en sitio (imports, formato, EOL)
+
+use super::{
+    blocking, cancelled_note, results_schema, stats_schema, tool_result, McpTool, ToolContext,
+};
+use crate::analyzer::CodeAnalyzer;
+use crate::error::Result;
+use crate::mcp::auth::Scope;
+use crate::parallel_engine::{self, ParallelConfig};
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+
+pub struct RepairTool;
+
+impl McpTool for RepairTool {
+    fn name(&self) -> &str {
+        "repair"
+    }
+
+    fn description(&self) -> &str {
+        "🛠️ Reparación paralela: imports duplicados, formato, EOL, espacios."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "extension": { "type": "string", "default": "rs" },
+                "dry_run": { "type": "boolean", "default": false }
+            },
+            "required": ["path"],
+            "additionalProperties": false
+        })
+    }
+
+    fn output_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "dry_run": { "type": "boolean" },
+                "stats": stats_schema(),
+                "results": results_schema()
+            },
+            "required": ["path", "dry_run", "stats", "results"]
+        }))
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({
+            "title": "Reparar archivos",
+            "readOnlyHint": false,
+            "destructiveHint": true,
+            "idempotentHint": true,
+            "openWorldHint": false
+        }))
+    }
+
+    fn scope(&self) -> Scope {
+        Scope::Edit
+    }
+
+    fn call(&self, arguments: Value, ctx: ToolContext) -> BoxFuture<'static, Result<Value>> {
+        blocking(move || run(&arguments, ctx))
+    }
+}
+
+fn run(arguments: &Value, ctx: ToolContext) -> Result<Value> {
+    let path = &ctx.confine(
+        arguments
+            .get("path")
+            .and_then(|v| v.as_str())
+            .unwrap_or("."),
+    )?;
+    let ext = arguments
+        .get("extension")
+        .and_then(|v| v.as_str())
+        .unwrap_or("rs");
+    let dry_run = arguments
+        .get("dry_run")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let config = ParallelConfig {
+        monitor: Some(ctx.monitor),
+        ..Default::default()
+    };
+
+    let files = CodeAnalyzer::scan_files(path, ext, true, false)?;
+    let (res, stats) = parallel_engine::ultra_repair(&files, config, dry_run)?;
+    Ok(tool_result(
+        format!(
+            "🛠️ Repair{} en {}ms. Archivos: {} (reparados: {}){}",
+            if dry_run { " [DRY]" } else { "" },
+            stats.total_duration_ms,
+            stats.total_files,
+            stats.successful,
+            cancelled_note(stats.cancelled)
+        ),
+        json!({ "path": path, "dry_run": dry_run, "stats": stats, "results": res }),
+    ))
+}
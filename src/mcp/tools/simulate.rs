@@ -0,0 +1,189 @@
+//! simulate.rs - Tool `simulate`: mega simulaciones por fase o simulación Bend custom
+
+use super::{blocking, tool_result, McpTool, ToolContext};
+use crate::error::{MemoryPError, Result};
+use crate::mcp::auth::Scope;
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+/// Los `.bend` custom se escriben aquí, bajo la primera raíz, y se borran al terminar
+const SIM_DIR: &str = ".memory_p_sims";
+/// Nombre custom: un identificador, nunca una ruta
+const NAME_PATTERN: &str = "^[A-Za-z0-9_-]+$";
+
+pub struct SimulateTool;
+
+impl McpTool for SimulateTool {
+    fn name(&self) -> &str {
+        "simulate"
+    }
+
+    fn description(&self) -> &str {
+        "🌀 Mega simulaciones: Phase1=15K/módulo, Phase2=150K paralelismo, Phase3=500K ecosystem."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "phase": { "type": "integer", "enum": [1, 2, 3], "default": 1, "description": "1=módulos, 2=paralelismo, 3=ecosystem (ignorado con name + logic)" },
+                "iterations": { "type": "integer", "minimum": 1, "default": 1000, "description": "Simulaciones por config" },
+                "modules": { "type": "array", "items": { "type": "string" }, "description": "Para phase 1" },
+                "use_gpu": { "type": "boolean", "default": false },
+                "name": { "type": "string", "pattern": NAME_PATTERN, "description": "Nombre de simulación custom (letras, dígitos, _ y -)" },
+                "logic": { "type": "string", "description": "Código Bend custom" }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    fn output_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "description": "Phase 1-3, o {name, output} para simulaciones custom",
+            "properties": {
+                "phase": { "type": "integer" },
+                "total_sims": { "type": "integer" },
+                "completed": { "type": "integer" },
+                "cancelled": { "type": "boolean" },
+                "duration_ms": { "type": "integer" },
+                "best_config": { "type": "object" },
+                "improvements": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "target": { "type": "string" },
+                            "metric": { "type": "string" },
+                            "before": { "type": "number" },
+                            "after": { "type": "number" },
+                            "improvement_pct": { "type": "number" }
+                        }
+                    }
+                },
+                "name": { "type": "string" },
+                "output": { "type": "string" }
+            }
+        }))
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({
+            "title": "Mega simulación",
+            "readOnlyHint": false,
+            // Las simulaciones custom escriben y borran su `.bend` y llaman a WSL
+            "destructiveHint": true,
+            "idempotentHint": false,
+            "openWorldHint": true
+        }))
+    }
+
+    fn progress_unit(&self) -> &'static str {
+        "simulaciones"
+    }
+
+    fn scope(&self) -> Scope {
+        Scope::Simulate
+    }
+
+    fn call(&self, arguments: Value, ctx: ToolContext) -> BoxFuture<'static, Result<Value>> {
+        blocking(move || run(&arguments, ctx))
+    }
+}
+
+fn run(arguments: &Value, ctx: ToolContext) -> Result<Value> {
+    let phase = arguments.get("phase").and_then(|v| v.as_i64()).unwrap_or(1);
+    let iterations = arguments
+        .get("iterations")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(1000) as usize;
+    let use_gpu = arguments
+        .get("use_gpu")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    // Check for custom simulation
+    if let (Some(name), Some(logic)) = (
+        arguments.get("name").and_then(|v| v.as_str()),
+        arguments.get("logic").and_then(|v| v.as_str()),
+    ) {
+        let path = custom_sim_path(name, &ctx)?;
+        let output =
+            crate::simulation_engine::run_bend_simulation(&path, logic, &json!({}), use_gpu)?;
+        return Ok(tool_result(
+            format!("🌀 Custom Sim:\n{}", output),
+            json!({ "name": name, "output": output }),
+        ));
+    }
+
+    // Phase-based mega simulation with actual execution
+    let config = crate::mega_simulator::SimConfig {
+        phase: u8::try_from(phase)
+            .map_err(|_| MemoryPError::InvalidParams(format!("Invalid phase: {}", phase)))?,
+        iterations,
+        modules: arguments
+            .get("modules")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        use_gpu,
+        context7_enabled: true,
+        monitor: Some(ctx.monitor),
+    };
+
+    let result = crate::mega_simulator::run_mega_simulation(config)?;
+    // Save results to file (una corrida cancelada no pisa resultados completos)
+    let result_path = format!("phase{}_results.json", phase);
+    if result.cancelled {
+        tracing::info!("⛔ Phase {} cancelada, no se guarda {}", phase, result_path);
+    } else if let Err(e) =
+        crate::mega_simulator::save_results(&result, std::path::Path::new(&result_path))
+    {
+        tracing::warn!("⚠️ No se pudo guardar {}: {}", result_path, e);
+    }
+
+    let improvements_summary: Vec<String> = result
+        .improvements
+        .iter()
+        .map(|i| format!("{}: {:.1}% improvement", i.target, i.improvement_pct))
+        .collect();
+
+    let summary = format!(
+        "🌀 Phase {} {}!\n⏱️ {}ms | 📊 {}/{} sims\n\n📈 Improvements:\n{}",
+        result.phase,
+        if result.cancelled {
+            "Cancelled (partial)"
+        } else {
+            "Complete"
+        },
+        result.duration_ms,
+        result.completed,
+        result.total_sims,
+        improvements_summary.join("\n")
+    );
+    Ok(tool_result(summary, serde_json::to_value(&result)?))
+}
+
+/// `{raíz}/.memory_p_sims/{name}.bend`; `name` ya pasó por `NAME_PATTERN`, pero se
+/// vuelve a confinar por si la tool se llama sin validar el esquema
+fn custom_sim_path(name: &str, ctx: &ToolContext) -> Result<PathBuf> {
+    if !regex::Regex::new(NAME_PATTERN).is_ok_and(|re| re.is_match(name)) {
+        return Err(MemoryPError::InvalidParams(format!(
+            "Nombre de simulación inválido: {}",
+            name
+        )));
+    }
+    let root = ctx
+        .roots
+        .first()
+        .ok_or_else(|| MemoryPError::PathNotAllowed(PathBuf::from(name)))?;
+    let dir = root.join(SIM_DIR);
+    std::fs::create_dir_all(&dir)?;
+    let path = ctx.confine(&dir.join(format!("{}.bend", name)).to_string_lossy())?;
+    Ok(PathBuf::from(path))
+}
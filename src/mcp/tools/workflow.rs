@@ -0,0 +1,174 @@
+//! workflow.rs - Tool `workflow`: pipeline Scan → Filter → Analyze → Edit → Repair → Evolve
+
+use super::{
+    blocking, cancelled_note, results_schema, stats_schema, tool_result, McpTool, ToolContext,
+};
+use crate::config::CONFIG;
+use crate::error::{MemoryPError, Result};
+use crate::mcp::auth::Scope;
+use crate::mcp::models::{UltraWorkflowRequest, WorkflowStep};
+use crate::parallel_engine;
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+const WORKFLOWS_DIR: &str = "PAYLOAD_BANK/WORKFLOWS";
+
+pub struct WorkflowTool;
+
+impl McpTool for WorkflowTool {
+    fn name(&self) -> &str {
+        "workflow"
+    }
+
+    fn description(&self) -> &str {
+        "🌊 Pipeline: Scan → Filter → Analyze → Edit → Repair → Evolve (auto-fix loop)."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "steps": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "action": { "type": "string", "enum": ["Scan", "Filter", "Analyze", "Edit", "Repair", "Evolve"] },
+                            "params": { "type": "object" }
+                        },
+                        "required": ["action"],
+                        "additionalProperties": false
+                    }
+                },
+                "name": { "type": "string", "description": "Workflow guardado en PAYLOAD_BANK/WORKFLOWS (sin .json); se usa si no vienen `steps`" },
+                "dry_run": { "type": "boolean", "default": true },
+                "max_tasks": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": CONFIG.parallelism.threads.max(1),
+                    "description": "Hilos de Rayon (como mucho `[parallelism] threads`)"
+                }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    fn output_schema(&self) -> Option<Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "steps": { "type": "integer" },
+                "stats": stats_schema(),
+                "results": results_schema()
+            },
+            "required": ["steps", "stats", "results"]
+        }))
+    }
+
+    fn annotations(&self) -> Option<Value> {
+        Some(json!({
+            "title": "Pipeline de workflow",
+            "readOnlyHint": false,
+            "destructiveHint": true,
+            "idempotentHint": false,
+            "openWorldHint": false
+        }))
+    }
+
+    fn scope(&self) -> Scope {
+        Scope::Edit
+    }
+
+    fn call(&self, arguments: Value, ctx: ToolContext) -> BoxFuture<'static, Result<Value>> {
+        blocking(move || run(&arguments, ctx))
+    }
+}
+
+/// Workflows guardados con `steps`, por nombre de archivo (sin `.json`)
+pub fn saved_workflows() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(WORKFLOWS_DIR) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .filter(|p| load_saved(p).is_ok_and(|args| args.get("steps").is_some()))
+        .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Argumentos de un workflow guardado: el archivo puede ser el request JSON-RPC completo
+fn load_saved(path: &Path) -> Result<Value> {
+    let saved: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    Ok(match saved.pointer("/params/arguments") {
+        Some(args) => args.clone(),
+        None => saved,
+    })
+}
+
+/// Argumentos de un workflow desde un archivo o, si no existe, del guardado con ese nombre (CLI)
+pub fn load(spec: &str) -> Result<Value> {
+    let path = Path::new(spec);
+    if path.is_file() {
+        return load_saved(path);
+    }
+    resolve_arguments(&json!({ "name": spec }))
+}
+
+/// `steps` explícitos o, si no vienen, los del workflow guardado `name`
+fn resolve_arguments(arguments: &Value) -> Result<Value> {
+    if arguments.get("steps").is_some() {
+        return Ok(arguments.clone());
+    }
+    let name = arguments
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            MemoryPError::InvalidParams("Falta 'steps' o 'name' (workflow guardado)".to_string())
+        })?;
+    if !saved_workflows().iter().any(|saved| saved == name) {
+        return Err(MemoryPError::InvalidParams(format!(
+            "Workflow guardado desconocido: {}",
+            name
+        )));
+    }
+    let mut resolved = load_saved(&Path::new(WORKFLOWS_DIR).join(format!("{}.json", name)))?;
+    // Lo que pasa el cliente (dry_run, max_tasks) pisa lo guardado
+    if let (Some(resolved), Some(overrides)) = (resolved.as_object_mut(), arguments.as_object()) {
+        for (key, value) in overrides.iter().filter(|(k, _)| *k != "name") {
+            resolved.insert(key.clone(), value.clone());
+        }
+    }
+    Ok(resolved)
+}
+
+fn run(arguments: &Value, ctx: ToolContext) -> Result<Value> {
+    let mut req = serde_json::from_value::<UltraWorkflowRequest>(resolve_arguments(arguments)?)?;
+    for step in &mut req.steps {
+        if let WorkflowStep::Scan { path, .. } = step {
+            *path = ctx.confine(path)?;
+        }
+    }
+    let mut config = crate::config::AppConfig::load().to_parallel_config();
+    config.monitor = Some(ctx.monitor);
+    // Un workflow guardado no pasa por el schema: el tope se aplica también aquí
+    if let Some(max_tasks) = req.max_tasks {
+        config.max_threads = max_tasks.clamp(1, config.max_threads.max(1));
+    }
+    let (res, stats) = parallel_engine::ultra_workflow(&req, config)?;
+    Ok(tool_result(
+        format!(
+            "🌊 Workflow en {}ms. Pasos: {} (exitosos: {}){}",
+            stats.total_duration_ms,
+            req.steps.len(),
+            stats.successful,
+            cancelled_note(stats.cancelled)
+        ),
+        json!({ "steps": req.steps.len(), "stats": stats, "results": res }),
+    ))
+}
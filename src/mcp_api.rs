@@ -1,15 +1,89 @@
-use crate::analyzer::CodeAnalyzer;
-use crate::error::MemoryPError;
+use crate::error::{MemoryPError, Result, FORBIDDEN, SERVER_BUSY};
+use crate::mcp::auth::{self, Grant, Scope};
 use crate::mcp::handlers::*;
 use crate::mcp::models::*;
-use crate::parallel_engine::{self, ParallelConfig};
+use crate::mcp::progress::{self, ProgressReporter};
+use crate::mcp::tools::{self, ToolContext};
+use crate::mcp::{
+    admission, cancellation, completion, logging, metrics, origin, prompts, protocol, resources,
+    roots, schema, status,
+};
+use crate::parallel_engine::JobMonitor;
+
+use crate::mcp::session::{self, Session};
 
 use axum::{
+    http::{HeaderMap, HeaderValue, StatusCode},
+    middleware,
+    response::sse::{Event, KeepAlive, Sse},
+    response::{IntoResponse, Response},
     routing::{get, post},
-    Json, Router,
+    Extension, Json, Router,
 };
+use futures::stream::{self, Stream};
 use serde_json::{json, Value};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+/// Contexto del mensaje entrante. `notify` es el canal del transporte para notificaciones
+/// ligadas al request (progreso), que llegan al cliente antes de la respuesta.
+/// La sesión identifica al cliente (cancelaciones) y guarda la versión negociada.
+#[derive(Clone, Default)]
+pub struct RequestContext {
+    pub notify: Option<mpsc::UnboundedSender<Value>>,
+    pub session: Option<Arc<Session>>,
+    /// Cabecera `MCP-Protocol-Version` en HTTP sin sesión
+    pub protocol_version: Option<String>,
+    /// `id` del request en curso (lo fija `handle_single`)
+    pub request_id: Option<Value>,
+    /// Token que autenticó el request HTTP; `None` = sin restricciones (stdio o sin `[auth]`)
+    pub grant: Option<Grant>,
+}
+
+impl RequestContext {
+    /// Ámbito de los ids de request: la sesión, o el token en HTTP sin sesión.
+    /// Sin ninguno de los dos no hay forma de saber de quién es un id.
+    fn scope(&self) -> Option<String> {
+        match (&self.session, &self.grant) {
+            (Some(session), _) => Some(session.id.clone()),
+            (None, Some(grant)) => Some(format!("token:{}", grant.name)),
+            (None, None) => None,
+        }
+    }
+
+    /// Cliente para los límites de tasa: el token HTTP, si no la sesión
+    pub fn client(&self) -> String {
+        match (&self.grant, &self.session) {
+            (Some(grant), _) => format!("token:{}", grant.name),
+            (None, Some(session)) => format!("session:{}", session.id),
+            (None, None) => "anónimo".to_string(),
+        }
+    }
+
+    /// Exige un permiso al token del request; sin token (stdio o sin `[auth]`) todo vale
+    fn require(&self, scope: Scope) -> Result<()> {
+        match &self.grant {
+            Some(grant) => grant.require(scope),
+            None => Ok(()),
+        }
+    }
+
+    /// Versión del protocolo con la que se habla con este cliente
+    fn version(&self) -> String {
+        match &self.session {
+            Some(s) => s
+                .protocol_version()
+                .unwrap_or_else(|| protocol::LATEST_VERSION.to_string()),
+            None => self
+                .protocol_version
+                .clone()
+                .unwrap_or_else(|| protocol::DEFAULT_HTTP_VERSION.to_string()),
+        }
+    }
+}
 
 pub fn routes() -> Router {
     Router::new()
@@ -17,421 +91,654 @@ pub fn routes() -> Router {
             "/",
             get(mcp_descriptor_handler).post(mcp_descriptor_handler),
         )
-        .route("/mcp", post(mcp_json_rpc_handler))
+        .route(
+            "/mcp",
+            post(mcp_json_rpc_handler)
+                .get(mcp_stream_handler)
+                .delete(mcp_delete_session_handler),
+        )
         .route("/mcp/sse", get(mcp_sse_handler))
+        .route("/status", get(status_handler))
+        .route("/metrics", get(metrics_handler))
+        .route("/health", get(health_handler))
         .route("/create_project", post(create_project_handler))
         .route("/analyze_project", post(analyze_project_handler))
         .route("/edit_project", post(edit_project_handler))
         .route("/repair_project", post(repair_project_handler))
         .route("/ultra", post(ultra_engine_handler))
+        .route("/jobs", post(create_job_handler))
+        .route("/jobs/:id", get(job_handler).delete(delete_job_handler))
+        .route("/jobs/:id/events", get(job_events_handler))
+        // La última capa corre primero: CORS (preflight), luego Origin, luego el token
+        .layer(middleware::from_fn(auth::require_token))
+        .layer(middleware::from_fn(origin::validate))
+        .layer(origin::cors())
 }
 
-pub async fn mcp_json_rpc_handler(Json(req): Json<JsonRpcRequest>) -> Json<JsonRpcResponse> {
-    let id = req.id.clone().unwrap_or(Value::Null);
+/// Transporte Streamable HTTP (POST /mcp): un mensaje (objeto o batch) por request.
+/// `initialize` abre sesión (`Mcp-Session-Id`); sin cabecera se atiende en modo sin sesión.
+/// Los `tools/call` de una sesión que acepta `text/event-stream` se responden como stream SSE.
+/// Un `tools/call` sin permiso para la tool responde 403 (con el error JSON-RPC en el cuerpo).
+pub async fn mcp_json_rpc_handler(
+    grant: Option<Extension<Grant>>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let message: Value = match serde_json::from_str(&body) {
+        Ok(v) => v,
+        Err(e) => {
+            let err = MemoryPError::Parse(e.to_string());
+            return rpc_error_response(StatusCode::BAD_REQUEST, &err);
+        }
+    };
+
+    let session = match headers.get(session::SESSION_HEADER) {
+        Some(raw) => match raw.to_str().ok().and_then(session::get) {
+            Some(s) => Some(s),
+            None => {
+                let err = MemoryPError::InvalidRequest("Sesión desconocida o expirada".into());
+                return rpc_error_response(StatusCode::NOT_FOUND, &err);
+            }
+        },
+        None if has_method(&message, "initialize") => Some(session::create()),
+        None => None,
+    };
+
+    let protocol_version = match headers.get(protocol::VERSION_HEADER) {
+        Some(raw) => match raw.to_str().ok().filter(|v| protocol::is_supported(v)) {
+            Some(v) => Some(v.to_string()),
+            None => {
+                let err = MemoryPError::InvalidRequest(format!(
+                    "{} no soportada; versiones: {}",
+                    protocol::VERSION_HEADER,
+                    protocol::SUPPORTED_VERSIONS.join(", ")
+                ));
+                return rpc_error_response(StatusCode::BAD_REQUEST, &err);
+            }
+        },
+        None => None,
+    };
+    let ctx = RequestContext {
+        session: session.clone(),
+        protocol_version,
+        grant: grant.map(|Extension(grant)| grant),
+        ..Default::default()
+    };
+    let mut response = if !has_requests(&message) {
+        handle_value(message, &ctx).await;
+        StatusCode::ACCEPTED.into_response()
+    } else if let Some(s) = session
+        .as_ref()
+        .filter(|_| accepts(&headers, "text/event-stream") && has_method(&message, "tools/call"))
+    {
+        stream_replies(s.clone(), message, ctx).into_response()
+    } else {
+        match handle_value(message, &ctx).await {
+            Some(reply) => error_status(&reply),
+            None => StatusCode::ACCEPTED.into_response(),
+        }
+    };
 
+    if let Some(value) = session.and_then(|s| HeaderValue::from_str(&s.id).ok()) {
+        response
+            .headers_mut()
+            .insert(session::SESSION_HEADER, value);
+    }
+    response
+}
+
+/// Responde un POST como stream SSE: progreso y respuesta llevan id de evento reanudable
+fn stream_replies(
+    session: Arc<Session>,
+    message: Value,
+    ctx: RequestContext,
+) -> Sse<impl Stream<Item = std::result::Result<Event, Infallible>>> {
+    let stream_id = session.open_stream();
+    let (tx, rx) = mpsc::unbounded_channel::<Value>();
+    tokio::spawn(async move {
+        let ctx = RequestContext {
+            notify: Some(tx.clone()),
+            ..ctx
+        };
+        if let Some(reply) = handle_value(message, &ctx).await {
+            let _ = tx.send(reply);
+        }
+    });
+
+    let events = stream::unfold(rx, move |mut rx| {
+        let session = session.clone();
+        async move {
+            let msg = rx.recv().await?;
+            let event = session.record(stream_id, msg);
+            Some((Ok(sse_event(&event)), rx))
+        }
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Respuesta de un request suelto; sin permiso → 403, servidor ocupado → 429 con `Retry-After`
+fn error_status(reply: &Value) -> Response {
+    let code = reply.pointer("/error/code").and_then(|c| c.as_i64());
+    match code {
+        Some(FORBIDDEN) => (StatusCode::FORBIDDEN, Json(reply)).into_response(),
+        Some(SERVER_BUSY) => {
+            let retry_after = reply
+                .pointer("/error/data/retryAfter")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(1);
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(axum::http::header::RETRY_AFTER, retry_after.to_string())],
+                Json(reply),
+            )
+                .into_response()
+        }
+        _ => Json(reply).into_response(),
+    }
+}
+
+fn rpc_error_response(status: StatusCode, err: &MemoryPError) -> Response {
+    (status, Json(JsonRpcResponse::failure(Value::Null, err))).into_response()
+}
+
+/// Procesa un mensaje JSON-RPC crudo, común a stdio y HTTP.
+/// Devuelve `None` cuando no hay respuesta (solo notificaciones o respuestas del cliente).
+pub async fn handle_message(raw: &str, ctx: &RequestContext) -> Option<Value> {
+    match serde_json::from_str(raw) {
+        Ok(message) => handle_value(message, ctx).await,
+        Err(e) => {
+            let err = MemoryPError::Parse(e.to_string());
+            serde_json::to_value(JsonRpcResponse::failure(Value::Null, &err)).ok()
+        }
+    }
+}
+
+pub async fn handle_value(message: Value, ctx: &RequestContext) -> Option<Value> {
+    match message {
+        Value::Array(batch) if batch.is_empty() => {
+            let err = MemoryPError::InvalidRequest("Batch vacío".to_string());
+            serde_json::to_value(JsonRpcResponse::failure(Value::Null, &err)).ok()
+        }
+        Value::Array(_) if !protocol::has_batches(&ctx.version()) => {
+            let err = MemoryPError::InvalidRequest(format!(
+                "Batches JSON-RPC no soportados en la versión {}",
+                ctx.version()
+            ));
+            serde_json::to_value(JsonRpcResponse::failure(Value::Null, &err)).ok()
+        }
+        Value::Array(batch) => {
+            let replies: Vec<Value> =
+                futures::future::join_all(batch.into_iter().map(|m| handle_single(m, ctx)))
+                    .await
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|r| serde_json::to_value(r).ok())
+                    .collect();
+            if replies.is_empty() {
+                None
+            } else {
+                Some(Value::Array(replies))
+            }
+        }
+        single => handle_single(single, ctx)
+            .await
+            .and_then(|r| serde_json::to_value(r).ok()),
+    }
+}
+
+/// Elementos del mensaje (objeto o batch)
+fn message_items(message: &Value) -> Vec<&Value> {
+    match message {
+        Value::Array(batch) => batch.iter().collect(),
+        single => vec![single],
+    }
+}
+
+/// ¿Hay algún request (con `id`) que exija respuesta?
+fn has_requests(message: &Value) -> bool {
+    message_items(message)
+        .iter()
+        .any(|m| m.get("method").is_some() && m.get("id").is_some())
+}
+
+/// ¿Solo notificaciones `notifications/*` o respuestas del cliente? Se atienden al instante,
+/// sin esperar turno detrás de las tools en curso (p.ej. `notifications/cancelled`)
+pub fn is_control(message: &Value) -> bool {
+    message_items(message)
+        .iter()
+        .all(|m| match m.get("method").and_then(|v| v.as_str()) {
+            Some(method) => m.get("id").is_none() && method.starts_with("notifications/"),
+            None => m.get("id").is_some(),
+        })
+}
+
+fn has_method(message: &Value, method: &str) -> bool {
+    message_items(message)
+        .iter()
+        .any(|m| m.get("method").and_then(|v| v.as_str()) == Some(method))
+}
+
+/// Un elemento del mensaje: request (→ respuesta), notificación o respuesta del cliente (→ nada)
+async fn handle_single(message: Value, ctx: &RequestContext) -> Option<JsonRpcResponse> {
+    let Some(obj) = message.as_object() else {
+        let err = MemoryPError::InvalidRequest("Se esperaba un objeto JSON-RPC".to_string());
+        return Some(JsonRpcResponse::failure(Value::Null, &err));
+    };
+
+    // Sin `id` es notificación; `id: null` sigue siendo un request
+    let is_notification = !obj.contains_key("id");
+    let id = obj.get("id").cloned().unwrap_or(Value::Null);
+    if !matches!(id, Value::Null | Value::String(_) | Value::Number(_)) {
+        let err = MemoryPError::InvalidRequest("'id' debe ser string, número o null".to_string());
+        return Some(JsonRpcResponse::failure(Value::Null, &err));
+    }
+
+    if !obj.contains_key("method") && (obj.contains_key("result") || obj.contains_key("error")) {
+        // Respuesta del cliente a un request del servidor (roots/list...): nada que contestar
+        if !ctx.session.as_ref().is_some_and(|s| s.resolve(obj)) {
+            tracing::debug!("Respuesta del cliente sin request pendiente: {}", id);
+        }
+        return None;
+    }
+
+    let req: JsonRpcRequest = match serde_json::from_value(message) {
+        Ok(req) => req,
+        Err(e) => {
+            let err = MemoryPError::InvalidRequest(e.to_string());
+            return (!is_notification).then(|| JsonRpcResponse::failure(id, &err));
+        }
+    };
     if req.jsonrpc != "2.0" {
-        let err = MemoryPError::InvalidParams("Invalid JSON-RPC version".to_string());
-        return Json(JsonRpcResponse {
-            jsonrpc: "2.0".to_string(),
-            id,
-            result: None,
-            error: Some(json!({ "code": -32600, "message": format!("{}", err) })),
-        });
+        let err = MemoryPError::InvalidRequest("Invalid JSON-RPC version".to_string());
+        return (!is_notification).then(|| JsonRpcResponse::failure(id, &err));
     }
 
-    let method = req.method.as_str();
+    if is_notification {
+        handle_notification(&req.method, req.params.unwrap_or(Value::Null), ctx).await;
+        return None;
+    }
 
-    let result = match method {
-        "initialize" => Some(json!({
-            "protocolVersion": "2024-11-05",
-            "capabilities": {
-                "tools": { "listChanged": true },
-                "resources": { "listChanged": true, "subscribe": true },
-                "prompts": { "listChanged": true },
-                "logging": {}
-            },
-            "serverInfo": {
-                "name": "MEMORY_P_ULTRA",
-                "version": "2025.2.ULTRA",
-                "description": "Motor de procesamiento masivo paralelo nativo para Cursor, Windsurf y VS Code."
+    let ctx = RequestContext {
+        request_id: Some(id.clone()),
+        ..ctx.clone()
+    };
+    match handle_method(&req.method, req.params.unwrap_or(Value::Null), &ctx).await {
+        Ok(result) => Some(JsonRpcResponse::success(id, result)),
+        Err(err) => {
+            tracing::warn!("⚠️ {} falló: {}", req.method, err);
+            status::record_error(&req.method, &err);
+            Some(JsonRpcResponse::failure(id, &err))
+        }
+    }
+}
+
+/// Notificaciones del cliente: nunca generan respuesta
+async fn handle_notification(method: &str, params: Value, ctx: &RequestContext) {
+    match method {
+        "notifications/initialized" => {
+            tracing::info!("🤝 Cliente MCP inicializado");
+            if let Some(session) = ctx.session.as_ref().filter(|s| s.client_supports("roots")) {
+                tokio::spawn(roots::refresh(session.clone()));
             }
-        })),
-        "tools/list" | "listTools" => {
-            let tools = vec![
-                // === TOOL 1: analyze (combines ultra_analyze + ultra_overview) ===
-                Tool {
-                    name: "analyze".to_string(),
-                    description: "🔬 Análisis masivo paralelo con métricas, seguridad y overview arquitectónico.".to_string(),
-                    input_schema: json!({
-                        "type": "object",
-                        "properties": {
-                            "path": { "type": "string", "description": "Ruta al proyecto" },
-                            "mode": { "type": "string", "enum": ["deep", "quick", "overview"], "description": "deep=completo, quick=rápido, overview=arquitectura" },
-                            "extension": { "type": "string", "default": "rs" },
-                            "use_gitignore": { "type": "boolean", "default": true },
-                            "include_hidden": { "type": "boolean", "default": false }
-                        },
-                        "required": ["path"]
-                    }),
-                    annotations: None,
-                },
-                // === TOOL 2: repair ===
-                Tool {
-                    name: "repair".to_string(),
-                    description: "🛠️ Reparación paralela: imports duplicados, formato, EOL, espacios.".to_string(),
-                    input_schema: json!({
-                        "type": "object",
-                        "properties": {
-                            "path": { "type": "string" },
-                            "extension": { "type": "string", "default": "rs" },
-                            "dry_run": { "type": "boolean", "default": false }
-                        },
-                        "required": ["path"]
-                    }),
-                    annotations: None,
-                },
-                // === TOOL 3: edit (combines ultra_edit + ultra_delete) ===
-                Tool {
-                    name: "edit".to_string(),
-                    description: "✏️ Edición masiva atómica: replace, regex, append, delete.".to_string(),
-                    input_schema: json!({
-                        "type": "object",
-                        "properties": {
-                            "mode": { "type": "string", "enum": ["replace", "regex", "append", "delete"], "description": "Tipo de operación" },
-                            "changes": {
-                                "type": "array",
-                                "items": {
-                                    "type": "object",
-                                    "properties": {
-                                        "path": { "type": "string" },
-                                        "operations": { "type": "array" }
-                                    }
-                                }
-                            },
-                            "paths": { "type": "array", "items": { "type": "string" }, "description": "Para mode=delete" },
-                            "dry_run": { "type": "boolean", "default": true }
-                        },
-                        "required": ["mode"]
-                    }),
-                    annotations: None,
-                },
-                // === TOOL 4: workflow (with Evolve + Repair steps) ===
-                Tool {
-                    name: "workflow".to_string(),
-                    description: "🌊 Pipeline: Scan → Filter → Analyze → Edit → Repair → Evolve (auto-fix loop).".to_string(),
-                    input_schema: json!({
-                        "type": "object",
-                        "properties": {
-                            "steps": {
-                                "type": "array",
-                                "items": {
-                                    "type": "object",
-                                    "properties": {
-                                        "action": { "type": "string", "enum": ["Scan", "Filter", "Analyze", "Edit", "Repair", "Evolve"] },
-                                        "params": { "type": "object" }
-                                    }
-                                }
-                            },
-                            "dry_run": { "type": "boolean", "default": true },
-                            "max_threads": { "type": "integer" }
-                        },
-                        "required": ["steps"]
-                    }),
-                    annotations: None,
-                },
-                // === TOOL 5: simulate (3 phases: 15K/150K/500K) ===
-                Tool {
-                    name: "simulate".to_string(),
-                    description: "🌀 Mega simulaciones: Phase1=15K/módulo, Phase2=150K paralelismo, Phase3=500K ecosystem.".to_string(),
-                    input_schema: json!({
-                        "type": "object",
-                        "properties": {
-                            "phase": { "type": "integer", "enum": [1, 2, 3], "description": "1=módulos, 2=paralelismo, 3=ecosystem" },
-                            "iterations": { "type": "integer", "default": 1000, "description": "Simulaciones por config" },
-                            "modules": { "type": "array", "items": { "type": "string" }, "description": "Para phase 1" },
-                            "use_gpu": { "type": "boolean", "default": false },
-                            "name": { "type": "string", "description": "Nombre de simulación custom" },
-                            "logic": { "type": "string", "description": "Código Bend custom" }
-                        },
-                        "required": ["phase"]
-                    }),
-                    annotations: None,
-                },
-            ];
-            Some(json!({ "tools": tools }))
-        }
-        "tools/call" | "callTool" => {
-            let params = req.params.as_ref().unwrap();
-            let tool_name = params.get("name").unwrap().as_str().unwrap();
-            let arguments = params.get("arguments").unwrap();
-
-            match tool_name {
-                // === HANDLER 1: analyze (deep/quick/overview) ===
-                "analyze" => {
-                    let path = arguments
-                        .get("path")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or(".");
-                    let mode = arguments
-                        .get("mode")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("deep");
-                    let ext = arguments
-                        .get("extension")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("rs");
-                    let use_gitignore = arguments
-                        .get("use_gitignore")
-                        .and_then(|v| v.as_bool())
-                        .unwrap_or(true);
-                    let include_hidden = arguments
-                        .get("include_hidden")
-                        .and_then(|v| v.as_bool())
-                        .unwrap_or(false);
-
-                    match mode {
-                        "overview" => {
-                            let cargo_path = std::path::Path::new(path).join("Cargo.toml");
-                            let total_files =
-                                CodeAnalyzer::scan_files(path, ext, use_gitignore, include_hidden)
-                                    .map(|f| f.len())
-                                    .unwrap_or(0);
-                            let has_cargo = cargo_path.exists();
-                            Some(json!({ "content": [{ "type": "text", "text": format!(
-                                "🏛️ Overview: {} | Files: {} | Cargo.toml: {}",
-                                path, total_files, if has_cargo { "✅" } else { "❌" }
-                            )}]}))
-                        }
-                        _ => {
-                            let config = ParallelConfig::default();
-                            match CodeAnalyzer::scan_files(path, ext, use_gitignore, include_hidden)
-                            {
-                                Ok(files) => match parallel_engine::ultra_analyze(&files, config) {
-                                    Ok((_res, stats)) => Some(json!({
-                                        "content": [{ "type": "text", "text": format!(
-                                            "🔬 Analyze [{}] en {}ms. Archivos: {} (exitosos: {})",
-                                            mode, stats.total_duration_ms, stats.total_files, stats.successful
-                                        )}]
-                                    })),
-                                    Err(e) => Some(
-                                        json!({ "content": [{ "type": "text", "text": format!("Error: {}", e) }] }),
-                                    ),
-                                },
-                                Err(e) => Some(
-                                    json!({ "content": [{ "type": "text", "text": format!("Scan Error: {}", e) }] }),
-                                ),
-                            }
-                        }
-                    }
-                }
-                // === HANDLER 2: repair ===
-                "repair" => {
-                    let path = arguments
-                        .get("path")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or(".");
-                    let ext = arguments
-                        .get("extension")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("rs");
-                    let config = ParallelConfig::default();
-
-                    match CodeAnalyzer::scan_files(path, ext, true, false) {
-                        Ok(files) => match parallel_engine::ultra_repair(&files, config) {
-                            Ok((_res, stats)) => Some(json!({
-                                "content": [{ "type": "text", "text": format!(
-                                    "🛠️ Repair en {}ms. Archivos: {} (reparados: {})",
-                                    stats.total_duration_ms, stats.total_files, stats.successful
-                                )}]
-                            })),
-                            Err(e) => Some(
-                                json!({ "content": [{ "type": "text", "text": format!("Error: {}", e) }] }),
-                            ),
-                        },
-                        Err(e) => Some(
-                            json!({ "content": [{ "type": "text", "text": format!("Scan Error: {}", e) }] }),
-                        ),
-                    }
-                }
-                // === HANDLER 3: edit (replace/regex/append/delete) ===
-                "edit" => {
-                    let mode = arguments
-                        .get("mode")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("replace");
-                    let dry_run = arguments
-                        .get("dry_run")
-                        .and_then(|v| v.as_bool())
-                        .unwrap_or(true);
-
-                    if mode == "delete" {
-                        // Delete mode
-                        let paths_raw = arguments.get("paths").and_then(|v| v.as_array());
-                        let mut paths: Vec<PathBuf> = Vec::new();
-                        if let Some(arr) = paths_raw {
-                            for p in arr {
-                                if let Some(s) = p.as_str() {
-                                    paths.push(PathBuf::from(s));
-                                }
-                            }
-                        }
-                        let config = ParallelConfig::default();
-                        match parallel_engine::ultra_delete(&paths, config, dry_run) {
-                            Ok((_res, stats)) => Some(json!({
-                                "content": [{ "type": "text", "text": format!(
-                                    "🗑️ Delete {} en {}ms. Archivos: {} (eliminados: {})",
-                                    if dry_run { "[DRY]" } else { "[REAL]" },
-                                    stats.total_duration_ms, stats.total_files, stats.successful
-                                )}]
-                            })),
-                            Err(e) => Some(
-                                json!({ "content": [{ "type": "text", "text": format!("Error: {}", e) }] }),
-                            ),
-                        }
-                    } else {
-                        // Edit mode (replace/regex/append)
-                        match serde_json::from_value::<UltraEditRequest>(arguments.clone()) {
-                            Ok(req) => {
-                                let app_cfg = crate::config::AppConfig::load();
-                                let config = app_cfg.to_parallel_config();
-                                match parallel_engine::ultra_edit(&req.changes, config, dry_run) {
-                                    Ok((_res, stats)) => Some(json!({
-                                        "content": [{ "type": "text", "text": format!(
-                                            "✏️ Edit [{}] {} en {}ms. Archivos: {}",
-                                            mode, if dry_run { "[DRY]" } else { "[APPLIED]" },
-                                            stats.total_duration_ms, stats.total_files
-                                        )}]
-                                    })),
-                                    Err(e) => Some(
-                                        json!({ "content": [{ "type": "text", "text": format!("Error: {}", e) }] }),
-                                    ),
-                                }
-                            }
-                            Err(e) => Some(
-                                json!({ "content": [{ "type": "text", "text": format!("Invalid params: {}", e) }] }),
-                            ),
-                        }
-                    }
+        }
+        "notifications/roots/list_changed" => {
+            if let Some(session) = ctx.session.as_ref().filter(|s| s.client_supports("roots")) {
+                tokio::spawn(roots::refresh(session.clone()));
+            }
+        }
+        "notifications/cancelled" => {
+            let Some(request_id) = params.get("requestId") else {
+                return;
+            };
+            let reason = params
+                .get("reason")
+                .and_then(|v| v.as_str())
+                .unwrap_or("sin motivo");
+            // Puede llegar tarde (el request ya respondió): se ignora en silencio
+            let cancelled = ctx
+                .scope()
+                .is_some_and(|scope| cancellation::cancel(&scope, request_id));
+            if cancelled {
+                tracing::info!("⛔ Request {} cancelado: {}", request_id, reason);
+            }
+        }
+        m if m.starts_with("notifications/") => tracing::debug!("Notificación ignorada: {}", m),
+        // Un método normal enviado sin `id` se ejecuta igual, descartando el resultado
+        _ => {
+            if let Err(err) = handle_method(method, params, ctx).await {
+                tracing::warn!("⚠️ Notificación {} falló: {}", method, err);
+            }
+        }
+    }
+}
+
+/// Despacha un método JSON-RPC; los errores se convierten en el objeto `error` de la respuesta
+async fn handle_method(method: &str, params: Value, ctx: &RequestContext) -> Result<Value> {
+    match method {
+        "initialize" => {
+            let requested = params.get("protocolVersion").and_then(|v| v.as_str());
+            let version = protocol::negotiate(requested);
+            if let Some(session) = &ctx.session {
+                session.set_protocol_version(version);
+                session.set_client_capabilities(
+                    params.get("capabilities").cloned().unwrap_or(Value::Null),
+                );
+            }
+            tracing::info!(
+                "🤝 Protocolo MCP {} (pedido por el cliente: {})",
+                version,
+                requested.unwrap_or("-")
+            );
+            Ok(json!({
+                "protocolVersion": version,
+                "capabilities": capabilities(),
+                "serverInfo": {
+                    "name": "MEMORY_P_ULTRA",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "description": "Motor de procesamiento masivo paralelo nativo para Cursor, Windsurf y VS Code."
                 }
-                // === HANDLER 4: workflow (with Evolve) ===
-                "workflow" => {
-                    match serde_json::from_value::<UltraWorkflowRequest>(arguments.clone()) {
-                        Ok(req) => {
-                            let app_cfg = crate::config::AppConfig::load();
-                            let mut config = app_cfg.to_parallel_config();
-                            if let Some(max_tasks) = req.max_tasks {
-                                config.max_threads = max_tasks as usize;
-                            }
-                            match parallel_engine::ultra_workflow(&req, config) {
-                                Ok((_res, stats)) => Some(json!({
-                                    "content": [{ "type": "text", "text": format!(
-                                        "🌊 Workflow en {}ms. Pasos: {} (exitosos: {})",
-                                        stats.total_duration_ms, req.steps.len(), stats.successful
-                                    )}]
-                                })),
-                                Err(e) => Some(
-                                    json!({ "content": [{ "type": "text", "text": format!("Error: {}", e) }] }),
-                                ),
-                            }
-                        }
-                        Err(e) => Some(
-                            json!({ "content": [{ "type": "text", "text": format!("Invalid params: {}", e) }] }),
-                        ),
+            }))
+        }
+        "tools/list" | "listTools" => {
+            let version = ctx.version();
+            let tools: Vec<Tool> = tools::definitions()
+                .into_iter()
+                .map(|mut tool| {
+                    if !protocol::has_tool_annotations(&version) {
+                        tool.annotations = None;
                     }
-                }
-                // === HANDLER 5: simulate (3 phases) ===
-                "simulate" => {
-                    let phase = arguments.get("phase").and_then(|v| v.as_i64()).unwrap_or(1);
-                    let iterations = arguments
-                        .get("iterations")
-                        .and_then(|v| v.as_i64())
-                        .unwrap_or(1000) as usize;
-                    let use_gpu = arguments
-                        .get("use_gpu")
-                        .and_then(|v| v.as_bool())
-                        .unwrap_or(false);
-
-                    // Check for custom simulation
-                    if let (Some(name), Some(logic)) = (
-                        arguments.get("name").and_then(|v| v.as_str()),
-                        arguments.get("logic").and_then(|v| v.as_str()),
-                    ) {
-                        match crate::simulation_engine::run_bend_simulation(
-                            name,
-                            logic,
-                            &json!({}),
-                            use_gpu,
-                        ) {
-                            Ok(output) => Some(
-                                json!({ "content": [{ "type": "text", "text": format!("🌀 Custom Sim:\n{}", output) }] }),
-                            ),
-                            Err(e) => Some(
-                                json!({ "content": [{ "type": "text", "text": format!("Sim Error: {}", e) }] }),
-                            ),
-                        }
-                    } else {
-                        // Phase-based mega simulation with actual execution
-                        let config = crate::mega_simulator::SimConfig {
-                            phase: phase as u8,
-                            iterations,
-                            modules: arguments
-                                .get("modules")
-                                .and_then(|v| v.as_array())
-                                .map(|arr| {
-                                    arr.iter()
-                                        .filter_map(|v| v.as_str().map(String::from))
-                                        .collect()
-                                })
-                                .unwrap_or_default(),
-                            use_gpu,
-                            context7_enabled: true,
-                        };
-
-                        match crate::mega_simulator::run_mega_simulation(config) {
-                            Ok(result) => {
-                                // Save results to file
-                                let result_path = format!("phase{}_results.json", phase);
-                                let _ = crate::mega_simulator::save_results(
-                                    &result,
-                                    std::path::Path::new(&result_path),
-                                );
-
-                                let improvements_summary: Vec<String> = result
-                                    .improvements
-                                    .iter()
-                                    .map(|i| {
-                                        format!(
-                                            "{}: {:.1}% improvement",
-                                            i.target, i.improvement_pct
-                                        )
-                                    })
-                                    .collect();
-
-                                Some(json!({ "content": [{ "type": "text", "text": format!(
-                                    "🌀 Phase {} Complete!\n⏱️ {}ms | 📊 {}/{} sims\n\n📈 Improvements:\n{}",
-                                    result.phase,
-                                    result.duration_ms,
-                                    result.completed,
-                                    result.total_sims,
-                                    improvements_summary.join("\n")
-                                )}]}))
-                            }
-                            Err(e) => Some(
-                                json!({ "content": [{ "type": "text", "text": format!("Sim Error: {}", e) }] }),
-                            ),
-                        }
+                    if !protocol::has_structured_output(&version) {
+                        tool.output_schema = None;
                     }
-                }
-                _ => Some(json!({ "content": [{ "type": "text", "text": "Tool no encontrada" }] })),
+                    tool
+                })
+                .collect();
+            Ok(json!({ "tools": tools }))
+        }
+        "ping" => Ok(json!({})),
+        "tools/call" | "callTool" => call_tool(params, ctx).await,
+        // Reportes, el banco de payloads, prompts y el árbol de archivos: lectura como `analyze`
+        "resources/list" => {
+            ctx.require(Scope::Read)?;
+            Ok(json!({ "resources": resources::list() }))
+        }
+        "resources/read" => {
+            ctx.require(Scope::Read)?;
+            resources::read(str_param(&params, "uri")?)
+        }
+        "resources/subscribe" => {
+            ctx.require(Scope::Read)?;
+            resources::subscribe(str_param(&params, "uri")?, ctx.session.as_ref())?;
+            Ok(json!({}))
+        }
+        "resources/unsubscribe" => {
+            resources::unsubscribe(str_param(&params, "uri")?, ctx.session.as_ref());
+            Ok(json!({}))
+        }
+        "logging/setLevel" => {
+            let level = logging::parse_level(str_param(&params, "level")?)?;
+            match &ctx.session {
+                Some(session) => session.set_log_level(level),
+                None => logging::set_sessionless_level(level),
             }
+            Ok(json!({}))
+        }
+        "prompts/list" => {
+            ctx.require(Scope::Read)?;
+            Ok(json!({ "prompts": prompts::list() }))
         }
+        "prompts/get" => {
+            ctx.require(Scope::Read)?;
+            let name = str_param(&params, "name")?;
+            let arguments: HashMap<String, String> = params
+                .get("arguments")
+                .and_then(|v| v.as_object())
+                .map(|obj| {
+                    obj.iter()
+                        .map(|(k, v)| {
+                            let text = v
+                                .as_str()
+                                .map(String::from)
+                                .unwrap_or_else(|| v.to_string());
+                            (k.clone(), text)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            prompts::get(name, &arguments)
+        }
+        "completion/complete" => {
+            ctx.require(Scope::Read)?;
+            let roots = roots::for_session(ctx.session.as_ref()).await;
+            // Recorre el disco (rutas, extensiones): fuera del runtime async
+            tokio::task::spawn_blocking(move || completion::complete(&params, &roots))
+                .await
+                .map_err(|e| MemoryPError::Other(format!("La completion abortó: {}", e)))?
+        }
+        _ => Err(MemoryPError::MethodNotFound(method.to_string())),
+    }
+}
+
+/// Capacidades según lo que el servidor realmente atiende
+fn capabilities() -> Value {
+    let mut caps = serde_json::Map::new();
+    // `tools::register` en caliente emite notifications/tools/list_changed
+    if !tools::definitions().is_empty() {
+        caps.insert("tools".into(), json!({ "listChanged": true }));
+    }
+    // resources/* y prompts/* siempre se atienden; ambos vigilan cambios (watchers)
+    caps.insert(
+        "resources".into(),
+        json!({ "subscribe": true, "listChanged": true }),
+    );
+    caps.insert("prompts".into(), json!({ "listChanged": true }));
+    caps.insert("logging".into(), json!({}));
+    caps.insert("completions".into(), json!({}));
+    // `ref/tool` en completion/complete no es un tipo de ref de MCP: extensión propia
+    caps.insert(
+        "experimental".into(),
+        json!({ "memory-p/toolCompletions": { "refType": "ref/tool" } }),
+    );
+    Value::Object(caps)
+}
+
+/// Parámetro string obligatorio de `params`
+fn str_param<'a>(params: &'a Value, key: &str) -> Result<&'a str> {
+    params
+        .get(key)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| MemoryPError::InvalidParams(format!("Falta el parámetro '{}'", key)))
+}
+
+/// Ejecuta una tool del registro con sus `arguments` validados contra `inputSchema` (defaults incluidos).
+/// Errores de argumentos → error JSON-RPC; fallos de ejecución → resultado con `isError: true`.
+/// Con `_meta.progressToken` y un canal disponible, reporta el avance mientras corre.
+async fn call_tool(params: Value, ctx: &RequestContext) -> Result<Value> {
+    let tool_name = str_param(&params, "name")?.to_string();
+    let mut arguments = match params.get("arguments") {
+        None | Some(Value::Null) => json!({}),
+        Some(args) if args.is_object() => args.clone(),
+        Some(_) => {
+            return Err(MemoryPError::InvalidParams(
+                "'arguments' debe ser un objeto".to_string(),
+            ))
+        }
+    };
+    let tool = tools::get(&tool_name)
+        .ok_or_else(|| MemoryPError::InvalidParams(format!("Tool desconocida: {}", tool_name)))?;
+    ctx.require(tool.scope())?;
+    schema::validate(&tool.input_schema(), &mut arguments)?;
+    // El turno se suelta al terminar la tool
+    let _admission = if tool.heavy() {
+        Some(admission::admit(&ctx.client()).await?)
+    } else {
+        None
+    };
+
+    let monitor = Arc::new(JobMonitor::default());
+    let _in_flight = ctx
+        .request_id
+        .as_ref()
+        .map(|id| cancellation::register(ctx.scope().as_deref(), id, monitor.clone()));
+    let reporter = match (progress::token(&params), &ctx.notify) {
+        (Some(token), Some(sink)) => Some(ProgressReporter::spawn(
+            token,
+            monitor.clone(),
+            sink.clone(),
+            tool.progress_unit(),
+        )),
         _ => None,
     };
 
-    Json(JsonRpcResponse {
-        jsonrpc: "2.0".to_string(),
-        id,
-        result,
-        error: None,
+    let roots = roots::for_session(ctx.session.as_ref()).await;
+    let started = Instant::now();
+    let outcome = tool
+        .call(
+            arguments,
+            ToolContext {
+                monitor: monitor.clone(),
+                roots,
+                grant: ctx.grant.clone(),
+            },
+        )
+        .await;
+    metrics::record_call(&tool_name, &monitor, started.elapsed(), &outcome);
+    if let Some(reporter) = reporter {
+        reporter.finish().await;
+    }
+
+    match outcome {
+        Ok(mut result) => {
+            if !protocol::has_structured_output(&ctx.version()) {
+                structured_as_text(&mut result);
+            }
+            Ok(result)
+        }
+        Err(
+            err @ (MemoryPError::InvalidParams(_)
+            | MemoryPError::Json(_)
+            | MemoryPError::PathNotAllowed(_)),
+        ) => Err(err),
+        Err(err) => {
+            tracing::warn!("⚠️ Tool {} falló: {}", tool_name, err);
+            status::record_error(&tool_name, &err);
+            Ok(tool_error(&err))
+        }
+    }
+}
+
+/// Clientes previos a 2025-06-18 no conocen structuredContent: pasa como JSON en un bloque de texto
+fn structured_as_text(result: &mut Value) {
+    let Some(structured) = result
+        .as_object_mut()
+        .and_then(|obj| obj.remove("structuredContent"))
+    else {
+        return;
+    };
+    if let Some(content) = result.get_mut("content").and_then(|c| c.as_array_mut()) {
+        content.push(json!({ "type": "text", "text": structured.to_string() }));
+    }
+}
+
+fn tool_error(err: &MemoryPError) -> Value {
+    json!({
+        "content": [{ "type": "text", "text": err.to_string() }],
+        "isError": true,
+        "_meta": { "error": { "code": err.rpc_code(), "data": err.rpc_data() } }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{INVALID_REQUEST, METHOD_NOT_FOUND, PARSE_ERROR};
+
+    #[test]
+    fn test_structured_content_becomes_text_block() {
+        let mut result = tools::tool_result("resumen".into(), json!({ "files": 3 }));
+        structured_as_text(&mut result);
+        assert!(result.get("structuredContent").is_none());
+        let content = result["content"].as_array().unwrap();
+        assert_eq!(content.len(), 2);
+        assert_eq!(content[0]["text"], "resumen");
+        let text = content[1]["text"].as_str().unwrap();
+        assert_eq!(serde_json::from_str::<Value>(text).unwrap(), json!({ "files": 3 }));
+    }
+
+    /// Cliente HTTP sin sesión con una versión que aún admite batches
+    fn batch_ctx() -> RequestContext {
+        RequestContext {
+            protocol_version: Some("2025-03-26".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_methods_need_read_scope() {
+        let ctx = RequestContext {
+            grant: Some(Grant {
+                name: "solo-simular".to_string(),
+                scopes: vec![Scope::Simulate],
+            }),
+            ..Default::default()
+        };
+        let calls = [
+            json!({ "method": "resources/list" }),
+            json!({ "method": "resources/read", "params": { "uri": "memory-p://simulations/phase1" } }),
+            json!({ "method": "prompts/list" }),
+            json!({ "method": "prompts/get", "params": { "name": "x" } }),
+            json!({ "method": "completion/complete",
+                    "params": { "ref": { "type": "ref/tool", "name": "analyze" },
+                                "argument": { "name": "path", "value": "/" } } }),
+        ];
+        for (id, mut call) in calls.into_iter().enumerate() {
+            call["jsonrpc"] = json!("2.0");
+            call["id"] = json!(id);
+            let reply = handle_value(call.clone(), &ctx).await.unwrap();
+            assert_eq!(reply["error"]["code"], FORBIDDEN, "{}", call["method"]);
+        }
+
+        let ping = json!({ "jsonrpc": "2.0", "id": 9, "method": "ping" });
+        assert_eq!(handle_value(ping, &ctx).await.unwrap()["result"], json!({}));
+    }
+
+    #[tokio::test]
+    async fn test_empty_batch_is_invalid_request() {
+        let reply = handle_message("[]", &batch_ctx()).await.unwrap();
+        assert_eq!(reply["error"]["code"], INVALID_REQUEST);
+        assert_eq!(reply["id"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_notifications_only_batch_has_no_reply() {
+        let raw = r#"[{"jsonrpc": "2.0", "method": "notifications/initialized"},
+                      {"jsonrpc": "2.0", "method": "notifications/cancelled", "params": {"requestId": 1}}]"#;
+        assert!(handle_message(raw, &batch_ctx()).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_mixed_batch_answers_each_request() {
+        let raw = r#"[{"jsonrpc": "2.0", "id": 1, "method": "ping"},
+                      {"jsonrpc": "2.0", "method": "notifications/initialized"},
+                      {"jsonrpc": "2.0", "id": "b", "method": "no/existe"},
+                      5]"#;
+        let reply = handle_message(raw, &batch_ctx()).await.unwrap();
+        let replies = reply.as_array().unwrap();
+        assert_eq!(replies.len(), 3);
+        assert_eq!(replies[0]["id"], 1);
+        assert_eq!(replies[0]["result"], json!({}));
+        assert_eq!(replies[1]["id"], "b");
+        assert_eq!(replies[1]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(replies[2]["id"], Value::Null);
+        assert_eq!(replies[2]["error"]["code"], INVALID_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_json_is_parse_error_with_null_id() {
+        let reply = handle_message(r#"{"jsonrpc": "2.0", "id": 1,"#, &batch_ctx())
+            .await
+            .unwrap();
+        assert_eq!(reply["error"]["code"], PARSE_ERROR);
+        assert_eq!(reply["id"], Value::Null);
+    }
+}
@@ -4,10 +4,13 @@
 //! Phase 3: 550K (500K+50K) - Ecosystem comparison with Context7
 
 use crate::error::{MemoryPError, Result};
+use crate::parallel_engine::JobMonitor;
 use rayon::prelude::*;
+use serde::Serialize;
 use std::collections::HashMap;
 use std::path::Path;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 
 /// Configuración de simulación
 #[allow(dead_code)]
@@ -18,6 +21,8 @@ pub struct SimConfig {
     pub modules: Vec<String>,
     pub use_gpu: bool,
     pub context7_enabled: bool,
+    /// Progreso observable (simulaciones completadas / total)
+    pub monitor: Option<Arc<JobMonitor>>,
 }
 
 impl Default for SimConfig {
@@ -28,12 +33,13 @@ impl Default for SimConfig {
             modules: vec![],
             use_gpu: false,
             context7_enabled: true,
+            monitor: None,
         }
     }
 }
 
 /// Resultado de simulación
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SimResult {
     pub phase: u8,
     pub total_sims: usize,
@@ -41,9 +47,11 @@ pub struct SimResult {
     pub best_config: HashMap<String, serde_json::Value>,
     pub improvements: Vec<SimImprovement>,
     pub duration_ms: u64,
+    /// Cancelada antes de recorrer toda la grilla: resultados parciales
+    pub cancelled: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SimImprovement {
     pub target: String,
     pub metric: String,
@@ -92,7 +100,7 @@ const SRC_MODULES: &[&str] = &[
     "mcp/handlers.rs",
 ];
 
-pub fn run_phase1(iterations_per_module: usize) -> Result<SimResult> {
+pub fn run_phase1(iterations_per_module: usize, monitor: &JobMonitor) -> Result<SimResult> {
     let start = std::time::Instant::now();
     let params = Phase1Params::default();
     let total_configs = params.buffer_sizes.len()
@@ -101,7 +109,9 @@ pub fn run_phase1(iterations_per_module: usize) -> Result<SimResult> {
         * params.batch_sizes.len();
 
     let total_sims = SRC_MODULES.len() * iterations_per_module.min(total_configs * 50);
-    let completed = AtomicUsize::new(0);
+    // El progreso avanza por punto de la grilla explorado
+    monitor.add_total(SRC_MODULES.len() * total_configs);
+    let completed = &monitor.processed;
 
     // Parallel simulation per module
     let module_results: Vec<_> = SRC_MODULES
@@ -112,6 +122,9 @@ pub fn run_phase1(iterations_per_module: usize) -> Result<SimResult> {
 
             // Grid search simulation
             for &buf_size in &params.buffer_sizes {
+                if monitor.is_cancelled() {
+                    break;
+                }
                 for &regex_cache in &params.regex_cache {
                     for &mmap_thresh in &params.mmap_thresholds {
                         for &batch_size in &params.batch_sizes {
@@ -143,6 +156,7 @@ pub fn run_phase1(iterations_per_module: usize) -> Result<SimResult> {
                 }
             }
 
+            tracing::info!("🌀 Phase 1: {} explorado (score {:.2})", module, best_score);
             (module.to_string(), best_score, best_config)
         })
         .collect();
@@ -169,6 +183,7 @@ pub fn run_phase1(iterations_per_module: usize) -> Result<SimResult> {
         best_config: final_config,
         improvements,
         duration_ms: start.elapsed().as_millis() as u64,
+        cancelled: monitor.is_cancelled(),
     })
 }
 
@@ -188,7 +203,7 @@ fn simulate_module_perf(
     };
 
     // Buffer size impact (optimal around 16-32KB)
-    let buf_factor = if buf_size >= 16384 && buf_size <= 32768 {
+    let buf_factor = if (16384..=32768).contains(&buf_size) {
         1.2
     } else {
         1.0
@@ -198,14 +213,14 @@ fn simulate_module_perf(
     let regex_factor = if regex_cache { 1.15 } else { 1.0 };
 
     // MMAP threshold impact (optimal 5-10MB)
-    let mmap_factor = if mmap_thresh >= 5_242_880 && mmap_thresh <= 10_485_760 {
+    let mmap_factor = if (5_242_880..=10_485_760).contains(&mmap_thresh) {
         1.1
     } else {
         1.0
     };
 
     // Batch size impact (optimal 100-500)
-    let batch_factor = if batch_size >= 100 && batch_size <= 500 {
+    let batch_factor = if (100..=500).contains(&batch_size) {
         1.25
     } else {
         1.0
@@ -240,7 +255,7 @@ impl Default for Phase2Params {
     }
 }
 
-pub fn run_phase2(iterations: usize) -> Result<SimResult> {
+pub fn run_phase2(iterations: usize, monitor: &JobMonitor) -> Result<SimResult> {
     let start = std::time::Instant::now();
     let params = Phase2Params::default();
 
@@ -253,14 +268,24 @@ pub fn run_phase2(iterations: usize) -> Result<SimResult> {
 
     let runs_per_config = iterations / total_configs;
     let total_sims = total_configs * runs_per_config.max(1);
-    let completed = AtomicUsize::new(0);
+    monitor.add_total(total_sims);
+    let completed = &monitor.processed;
 
     let mut best_score = 0.0f64;
     let mut best_config: HashMap<String, serde_json::Value> = HashMap::new();
 
     // Grid search parallelism
-    for &threads in &params.threads {
+    'grid: for &threads in &params.threads {
+        tracing::info!(
+            "🌀 Phase 2: {} hilos ({}/{} sims)",
+            threads,
+            completed.load(Ordering::Relaxed),
+            total_sims
+        );
         for &batch in &params.batch_sizes {
+            if monitor.is_cancelled() {
+                break 'grid;
+            }
             for &chunk_strat in &params.chunk_strategies {
                 for &io_mode in &params.io_modes {
                     for &stealing in &params.work_stealing {
@@ -320,6 +345,7 @@ pub fn run_phase2(iterations: usize) -> Result<SimResult> {
             improvement_pct: (best_score - 1.0) * 100.0,
         }],
         duration_ms: start.elapsed().as_millis() as u64,
+        cancelled: monitor.is_cancelled(),
     })
 }
 
@@ -435,7 +461,7 @@ pub fn get_ecosystem_comparisons() -> Vec<EcosystemComparison> {
     ]
 }
 
-pub fn run_phase3(iterations: usize) -> Result<SimResult> {
+pub fn run_phase3(iterations: usize, monitor: &JobMonitor) -> Result<SimResult> {
     let start = std::time::Instant::now();
     let comparisons = get_ecosystem_comparisons();
 
@@ -443,12 +469,17 @@ pub fn run_phase3(iterations: usize) -> Result<SimResult> {
 
     let sims_per_lib = iterations / total_libs;
     let total_sims = total_libs * sims_per_lib.max(1);
-    let completed = AtomicUsize::new(0);
+    monitor.add_total(total_sims);
+    let completed = &monitor.processed;
 
     let mut best_per_category: HashMap<String, serde_json::Value> = HashMap::new();
     let mut improvements = Vec::new();
 
     for comp in &comparisons {
+        if monitor.is_cancelled() {
+            break;
+        }
+        tracing::info!("🌀 Phase 3: comparando {}", comp.category);
         // Simulate current library
         let current_score = simulate_library(comp.current, comp.category);
 
@@ -503,6 +534,7 @@ pub fn run_phase3(iterations: usize) -> Result<SimResult> {
         best_config: best_per_category,
         improvements,
         duration_ms: start.elapsed().as_millis() as u64,
+        cancelled: monitor.is_cancelled(),
     })
 }
 
@@ -568,11 +600,12 @@ fn simulate_library(lib: &str, category: &str) -> f64 {
 // ============================================================================
 
 pub fn run_mega_simulation(config: SimConfig) -> Result<SimResult> {
+    let monitor = config.monitor.clone().unwrap_or_default();
     match config.phase {
-        1 => run_phase1(config.iterations),
-        2 => run_phase2(config.iterations),
-        3 => run_phase3(config.iterations),
-        _ => Err(MemoryPError::Other(format!(
+        1 => run_phase1(config.iterations, &monitor),
+        2 => run_phase2(config.iterations, &monitor),
+        3 => run_phase3(config.iterations, &monitor),
+        _ => Err(MemoryPError::InvalidParams(format!(
             "Invalid phase: {}",
             config.phase
         ))),
@@ -585,6 +618,7 @@ pub fn save_results(result: &SimResult, path: &Path) -> Result<()> {
         "phase": result.phase,
         "total_simulations": result.total_sims,
         "completed": result.completed,
+        "cancelled": result.cancelled,
         "duration_ms": result.duration_ms,
         "best_config": result.best_config,
         "improvements": result.improvements.iter().map(|i| {
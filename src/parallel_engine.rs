@@ -1,6 +1,7 @@
 // [nuclear_god_mode] PROCESSED AT MAX SPEED
 use crate::analyzer::CodeAnalyzer;
 use crate::error::{MemoryPError, Result};
+use crate::histogram::Histogram;
 use crate::workspace;
 use jwalk::WalkDir;
 use memmap2::Mmap;
@@ -9,10 +10,30 @@ use regex::Regex;
 use serde::Serialize;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 
+/// Hilos de todos los pools vivos, los que están en un archivo y su tiempo acumulado (`/metrics`)
+static POOL_THREADS: AtomicUsize = AtomicUsize::new(0);
+static BUSY_THREADS: AtomicUsize = AtomicUsize::new(0);
+static BUSY_MICROS: AtomicU64 = AtomicU64::new(0);
+
+/// Ocupación de los pools de rayon que arma cada tool
+pub struct PoolUsage {
+    pub threads: usize,
+    pub busy: usize,
+    pub busy_micros: u64,
+}
+
+pub fn pool_usage() -> PoolUsage {
+    PoolUsage {
+        threads: POOL_THREADS.load(Ordering::Relaxed),
+        busy: BUSY_THREADS.load(Ordering::Relaxed),
+        busy_micros: BUSY_MICROS.load(Ordering::Relaxed),
+    }
+}
+
 /// Configuración avanzada para el motor paralelo
 #[derive(Debug, Clone)]
 pub struct ParallelConfig {
@@ -22,6 +43,43 @@ pub struct ParallelConfig {
     pub _file_timeout_ms: u64,
     pub _continue_on_error: bool,
     pub _large_file_threshold: usize,
+    /// Progreso observable desde fuera mientras el trabajo corre
+    pub monitor: Option<Arc<JobMonitor>>,
+}
+
+/// Progreso compartido de un trabajo largo: el motor escribe, el transporte MCP lee.
+/// También lleva la señal de cancelación que el motor consulta antes de cada unidad de trabajo.
+#[derive(Debug, Default)]
+pub struct JobMonitor {
+    pub processed: Arc<AtomicUsize>,
+    pub total_bytes: Arc<AtomicUsize>,
+    total: AtomicUsize,
+    cancelled: AtomicBool,
+    /// Latencia de cada archivo procesado
+    pub file_latency: Histogram,
+}
+
+impl JobMonitor {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Suma unidades de trabajo al total (cada fase de un workflow agrega las suyas)
+    pub fn add_total(&self, n: usize) {
+        self.total.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// (hechos, total)
+    pub fn snapshot(&self) -> (usize, usize) {
+        (
+            self.processed.load(Ordering::Relaxed),
+            self.total.load(Ordering::Relaxed),
+        )
+    }
 }
 
 impl Default for ParallelConfig {
@@ -33,6 +91,7 @@ impl Default for ParallelConfig {
             _file_timeout_ms: 30000,
             _continue_on_error: true,
             _large_file_threshold: 10 * 1024 * 1024,
+            monitor: None,
         }
     }
 }
@@ -65,6 +124,8 @@ pub struct ProcessingStats {
     pub skipped: usize,
     pub total_bytes: usize,
     pub total_duration_ms: u64,
+    /// El trabajo se canceló: los resultados son parciales
+    pub cancelled: bool,
 }
 
 /// Motor ultra-paralelo de alto rendimiento
@@ -83,14 +144,44 @@ impl UltraParallelEngine {
             .build()
             .unwrap();
 
+        // Con monitor, los contadores del motor son los mismos que lee el transporte
+        let (processed_count, total_bytes) = match &config.monitor {
+            Some(m) => (m.processed.clone(), m.total_bytes.clone()),
+            None => Default::default(),
+        };
+
+        POOL_THREADS.fetch_add(pool.current_num_threads(), Ordering::Relaxed);
         Self {
             pool,
             config,
-            processed_count: Arc::new(AtomicUsize::new(0)),
-            total_bytes: Arc::new(AtomicUsize::new(0)),
+            processed_count,
+            total_bytes,
+        }
+    }
+
+    /// Mide un archivo mientras vive el guard: latencia en el monitor y ocupación del pool
+    fn time_file(&self) -> FileTimer<'_> {
+        BUSY_THREADS.fetch_add(1, Ordering::Relaxed);
+        FileTimer {
+            monitor: self.config.monitor.as_deref(),
+            start: Instant::now(),
         }
     }
 
+    /// Declara `n` archivos más por procesar en el monitor (si lo hay)
+    fn expect_files(&self, n: usize) {
+        if let Some(m) = &self.config.monitor {
+            m.add_total(n);
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.config
+            .monitor
+            .as_ref()
+            .is_some_and(|m| m.is_cancelled())
+    }
+
     pub fn process_files<F>(
         &self,
         paths: &[PathBuf],
@@ -100,6 +191,7 @@ impl UltraParallelEngine {
         F: Fn(&Path, &str) -> Result<(String, ProcessingStatus)> + Sync + Send,
     {
         let start = Instant::now();
+        self.expect_files(paths.len());
 
         // Cierre de lógica central para evitar duplicación
         let process_one = |path: &PathBuf| -> ProcessingResult {
@@ -151,14 +243,11 @@ impl UltraParallelEngine {
                 // Process Mmap Slice
                 self.total_bytes.fetch_add(size as usize, Ordering::Relaxed);
                 match operation(path, content) {
-                    Ok((msg, status)) => {
-                        self.processed_count.fetch_add(1, Ordering::Relaxed);
-                        ProcessingResult {
-                            path: path.display().to_string(),
-                            status,
-                            findings: vec![msg],
-                        }
-                    }
+                    Ok((msg, status)) => ProcessingResult {
+                        path: path.display().to_string(),
+                        status,
+                        findings: vec![msg],
+                    },
                     Err(e) => ProcessingResult {
                         path: path.display().to_string(),
                         status: ProcessingStatus::Error,
@@ -171,14 +260,11 @@ impl UltraParallelEngine {
                     Ok(content) => {
                         self.total_bytes.fetch_add(content.len(), Ordering::Relaxed);
                         match operation(path, &content) {
-                            Ok((msg, status)) => {
-                                self.processed_count.fetch_add(1, Ordering::Relaxed);
-                                ProcessingResult {
-                                    path: path.display().to_string(),
-                                    status,
-                                    findings: vec![msg],
-                                }
-                            }
+                            Ok((msg, status)) => ProcessingResult {
+                                path: path.display().to_string(),
+                                status,
+                                findings: vec![msg],
+                            },
                             Err(e) => ProcessingResult {
                                 path: path.display().to_string(),
                                 status: ProcessingStatus::Error,
@@ -195,16 +281,31 @@ impl UltraParallelEngine {
             }
         };
 
+        // Cada archivo atendido (con o sin error) cuenta como procesado.
+        // Tras una cancelación los archivos restantes ni se abren ni aparecen en el resultado.
+        let process_one = |path: &PathBuf| -> Option<ProcessingResult> {
+            if self.is_cancelled() {
+                return None;
+            }
+            let _timer = self.time_file();
+            let result = process_one(path);
+            self.processed_count.fetch_add(1, Ordering::Relaxed);
+            if result.status == ProcessingStatus::Error {
+                tracing::warn!("❌ {}: {}", result.path, result.findings.join(" | "));
+            }
+            Some(result)
+        };
+
         let results: Vec<ProcessingResult> = self.pool.install(|| {
             // ESTRATEGIA OPTIMIZADA POR SIMULACIÓN BEND
             // < 256 archivos: Overhead de chunks supera beneficio -> par_iter directo.
             // > 256 archivos: Chunks mejoran cache locality y reducen overhead -> par_chunks.
             if paths.len() < 256 {
-                paths.par_iter().map(process_one).collect()
+                paths.par_iter().filter_map(process_one).collect()
             } else {
                 paths
                     .par_chunks(self.config.chunk_size.max(1))
-                    .flat_map(|chunk| chunk.par_iter().map(process_one))
+                    .flat_map(|chunk| chunk.par_iter().filter_map(process_one))
                     .collect()
             }
         });
@@ -229,12 +330,35 @@ impl UltraParallelEngine {
                 .count(),
             total_bytes: self.total_bytes.load(Ordering::SeqCst),
             total_duration_ms: start.elapsed().as_millis() as u64,
+            cancelled: self.is_cancelled(),
         };
 
         Ok((results, stats))
     }
 }
 
+impl Drop for UltraParallelEngine {
+    fn drop(&mut self) {
+        POOL_THREADS.fetch_sub(self.pool.current_num_threads(), Ordering::Relaxed);
+    }
+}
+
+struct FileTimer<'a> {
+    monitor: Option<&'a JobMonitor>,
+    start: Instant,
+}
+
+impl Drop for FileTimer<'_> {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        BUSY_THREADS.fetch_sub(1, Ordering::Relaxed);
+        BUSY_MICROS.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+        if let Some(monitor) = self.monitor {
+            monitor.file_latency.observe(elapsed);
+        }
+    }
+}
+
 pub fn ultra_analyze(
     paths: &[PathBuf],
     config: ParallelConfig,
@@ -278,10 +402,11 @@ pub fn ultra_analyze(
 pub fn ultra_repair(
     paths: &[PathBuf],
     config: ParallelConfig,
+    dry_run: bool,
 ) -> Result<(Vec<ProcessingResult>, ProcessingStats)> {
     let engine = UltraParallelEngine::new(config);
     engine.process_files(paths, |path, _content| {
-        match workspace::smart_repair(path) {
+        match workspace::smart_repair(path, dry_run) {
             Ok(msg) => Ok((msg, ProcessingStatus::Success)),
             Err(e) => Err(MemoryPError::Other(e.to_string())),
         }
@@ -318,7 +443,7 @@ pub fn ultra_replace(
     engine.process_files(paths, |path, content| {
         if content.contains(&pat) {
             let modified = content.replace(&pat, &rep);
-            fs::write(path, modified).ok();
+            workspace::write_atomic(path, modified).ok();
             Ok(("Reemplazado".into(), ProcessingStatus::Success))
         } else {
             Ok(("Sin cambios".into(), ProcessingStatus::Skipped))
@@ -333,13 +458,19 @@ pub fn ultra_edit(
 ) -> Result<(Vec<ProcessingResult>, ProcessingStats)> {
     let engine = UltraParallelEngine::new(config);
     let start = Instant::now();
+    engine.expect_files(changes.len());
     use regex::Regex;
 
     // Paralelizamos sobre los archivos a cambiar
     let results: Vec<ProcessingResult> = engine.pool.install(|| {
         changes
             .par_iter()
+            .filter(|_| !engine.is_cancelled())
+            .inspect(|_| {
+                engine.processed_count.fetch_add(1, Ordering::Relaxed);
+            })
             .map(|change| {
+                let _timer = engine.time_file();
                 let path = Path::new(&change.path);
 
                 // Auto-create file if it doesn't exist
@@ -407,7 +538,7 @@ pub fn ultra_edit(
 
                 if applied > 0 {
                     if !dry_run {
-                        if let Err(e) = fs::write(path, &content) {
+                        if let Err(e) = workspace::write_atomic(path, &content) {
                             return ProcessingResult {
                                 path: change.path.clone(),
                                 status: ProcessingStatus::Error,
@@ -446,6 +577,7 @@ pub fn ultra_edit(
             .filter(|r| r.status == ProcessingStatus::Skipped)
             .count(),
         total_duration_ms: start.elapsed().as_millis() as u64,
+        cancelled: engine.is_cancelled(),
         ..Default::default()
     };
 
@@ -460,11 +592,17 @@ pub fn ultra_delete(
 ) -> Result<(Vec<ProcessingResult>, ProcessingStats)> {
     let engine = UltraParallelEngine::new(config);
     let start = Instant::now();
+    engine.expect_files(paths.len());
 
     let results: Vec<ProcessingResult> = engine.pool.install(|| {
         paths
             .par_iter()
+            .filter(|_| !engine.is_cancelled())
+            .inspect(|_| {
+                engine.processed_count.fetch_add(1, Ordering::Relaxed);
+            })
             .map(|path| {
+                let _timer = engine.time_file();
                 if !path.exists() {
                     return ProcessingResult {
                         path: path.display().to_string(),
@@ -520,6 +658,7 @@ pub fn ultra_delete(
             .filter(|r| r.status == ProcessingStatus::Skipped)
             .count(),
         total_duration_ms: start.elapsed().as_millis() as u64,
+        cancelled: engine.is_cancelled(),
         ..Default::default()
     };
 
@@ -538,6 +677,9 @@ pub fn ultra_workflow(
     use crate::mcp::models::WorkflowStep;
 
     for step in &request.steps {
+        if engine.is_cancelled() {
+            break;
+        }
         match step {
             WorkflowStep::Scan { path, extension } => {
                 let root = Path::new(path);
@@ -624,7 +766,8 @@ pub fn ultra_workflow(
                 stats.successful += st.successful;
             }
             WorkflowStep::Repair => {
-                let (mut res, st) = ultra_repair(&active_files, config.clone())?;
+                let (mut res, st) =
+                    ultra_repair(&active_files, config.clone(), request.dry_run.unwrap_or(false))?;
                 all_results.append(&mut res);
                 stats.successful += st.successful;
             }
@@ -641,7 +784,7 @@ pub fn ultra_workflow(
                     let mut fixes_applied = 0usize;
 
                     let (analysis_results, _) =
-                        engine.process_files(&active_files, |path, content| {
+                        engine.process_files(&active_files, |_path, content| {
                             let mut findings: Vec<String> = Vec::new();
 
                             // Detect fixable patterns
@@ -659,6 +802,19 @@ pub fn ultra_workflow(
                             Ok((findings.join("|"), ProcessingStatus::Success))
                         })?;
 
+                    // Un análisis cortado no dice nada sobre los issues restantes
+                    if engine.is_cancelled() {
+                        all_results.push(ProcessingResult {
+                            path: "EVOLVE_CANCELLED".into(),
+                            status: ProcessingStatus::Skipped,
+                            findings: vec![format!(
+                                "⛔ Cancelado en la iteración {}",
+                                iteration + 1
+                            )],
+                        });
+                        break;
+                    }
+
                     // Count issues
                     for res in &analysis_results {
                         issues_found += res
@@ -683,7 +839,7 @@ pub fn ultra_workflow(
                     // 2. Apply fixes (if not dry run)
                     if !is_dry {
                         let (repair_results, repair_stats) =
-                            ultra_repair(&active_files, config.clone())?;
+                            ultra_repair(&active_files, config.clone(), false)?;
                         fixes_applied = repair_stats.successful;
                         all_results.extend(repair_results);
                     }
@@ -704,6 +860,7 @@ pub fn ultra_workflow(
     }
 
     stats.total_duration_ms = start.elapsed().as_millis() as u64;
+    stats.cancelled = engine.is_cancelled();
     Ok((all_results, stats))
 }
 
@@ -724,7 +881,7 @@ impl ScanUtils {
             .filter(|e| e.file_type().is_file())
             .filter(|e| {
                 if let Some(ext) = extension {
-                    e.path().extension().map_or(false, |p_ext| p_ext == ext)
+                    e.path().extension().is_some_and(|p_ext| p_ext == ext)
                 } else {
                     true
                 }
@@ -7,6 +7,7 @@ use std::path::Path;
 use std::process::Command;
 
 /// Resultado de una simulación Bend
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct SimulationResult {
     pub name: String,
@@ -21,6 +22,7 @@ pub struct SimulationResult {
 pub enum SimulationMode {
     CpuC,        // bend run-c (compilado a C)
     GpuCuda,     // bend run-cu (CUDA GPU)
+    #[allow(dead_code)]
     Interpreted, // bend run (interpretado)
 }
 
@@ -34,17 +36,15 @@ impl std::fmt::Display for SimulationMode {
     }
 }
 
-/// Ejecuta una simulación Bend via WSL
+/// Ejecuta una simulación Bend via WSL; `path` es el `.bend` temporal (ya confinado)
 pub fn run_bend_simulation(
-    name: &str,
+    path: &Path,
     logic: &str,
     _params: &serde_json::Value,
     use_gpu: bool,
 ) -> Result<String> {
-    let filename = format!("{}.bend", name);
-
     // Escribir el archivo .bend
-    fs::write(&filename, logic).map_err(|e| MemoryPError::Other(format!("Write failed: {}", e)))?;
+    fs::write(path, logic).map_err(|e| MemoryPError::Other(format!("Write failed: {}", e)))?;
 
     let mode = if use_gpu {
         SimulationMode::GpuCuda
@@ -52,15 +52,16 @@ pub fn run_bend_simulation(
         SimulationMode::CpuC
     };
 
-    let result = execute_bend_via_wsl(&filename, mode)?;
+    let result = execute_bend_via_wsl(path, mode);
 
     // Cleanup
-    let _ = fs::remove_file(&filename);
+    let _ = fs::remove_file(path);
 
-    Ok(result)
+    result
 }
 
 /// Ejecuta un archivo Bend existente
+#[allow(dead_code)]
 pub fn run_bend_file(path: &Path, mode: SimulationMode) -> Result<SimulationResult> {
     let start = std::time::Instant::now();
     let name = path
@@ -69,7 +70,7 @@ pub fn run_bend_file(path: &Path, mode: SimulationMode) -> Result<SimulationResu
         .unwrap_or("unknown")
         .to_string();
 
-    let result = execute_bend_via_wsl(path.to_str().unwrap_or(""), mode);
+    let result = execute_bend_via_wsl(path, mode);
 
     let duration_ms = start.elapsed().as_millis() as u64;
 
@@ -92,6 +93,7 @@ pub fn run_bend_file(path: &Path, mode: SimulationMode) -> Result<SimulationResu
 }
 
 /// Escanea directorio por archivos .bend y los categoriza
+#[allow(dead_code)]
 pub fn scan_bend_simulations(dir: &Path) -> Result<Vec<BendSimulation>> {
     if !dir.exists() || !dir.is_dir() {
         return Err(MemoryPError::InvalidDirectory(dir.display().to_string()));
@@ -103,7 +105,7 @@ pub fn scan_bend_simulations(dir: &Path) -> Result<Vec<BendSimulation>> {
         let entry = entry?;
         let path = entry.path();
 
-        if path.extension().map_or(false, |e| e == "bend") {
+        if path.extension().is_some_and(|e| e == "bend") {
             let name = path
                 .file_stem()
                 .and_then(|s| s.to_str())
@@ -126,6 +128,7 @@ pub fn scan_bend_simulations(dir: &Path) -> Result<Vec<BendSimulation>> {
 }
 
 /// Información de una simulación Bend
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct BendSimulation {
     pub name: String,
@@ -135,6 +138,7 @@ pub struct BendSimulation {
 }
 
 /// Categorías de simulaciones
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SimulationCategory {
     Optimization,
@@ -156,6 +160,7 @@ impl std::fmt::Display for SimulationCategory {
     }
 }
 
+#[allow(dead_code)]
 fn categorize_simulation(name: &str, content: &str) -> SimulationCategory {
     let name_lower = name.to_lowercase();
 
@@ -172,7 +177,8 @@ fn categorize_simulation(name: &str, content: &str) -> SimulationCategory {
     }
 }
 
-fn execute_bend_via_wsl(filename: &str, mode: SimulationMode) -> Result<String> {
+/// Corre `bend` en el directorio del archivo, así la ruta que ve WSL es solo el nombre
+fn execute_bend_via_wsl(path: &Path, mode: SimulationMode) -> Result<String> {
     let bend_executable = "bend";
     let mode_arg = match mode {
         SimulationMode::GpuCuda => "run-cu",
@@ -180,11 +186,19 @@ fn execute_bend_via_wsl(filename: &str, mode: SimulationMode) -> Result<String>
         SimulationMode::Interpreted => "run",
     };
 
+    let filename = path
+        .file_name()
+        .map(|f| f.to_string_lossy().into_owned())
+        .unwrap_or_default();
     let cmd_str = format!("{} {} ./{}", bend_executable, mode_arg, filename);
 
     tracing::info!("🌀 Executing BEND [{}]: {}", mode, cmd_str);
 
-    let output = Command::new("wsl")
+    let mut command = Command::new("wsl");
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        command.current_dir(dir);
+    }
+    let output = command
         .arg("bash")
         .arg("-l")
         .arg("-c")
@@ -213,6 +227,7 @@ fn execute_bend_via_wsl(filename: &str, mode: SimulationMode) -> Result<String>
 }
 
 /// Ejecuta múltiples simulaciones en paralelo
+#[allow(dead_code)]
 pub fn run_batch_simulations(
     simulations: &[BendSimulation],
     mode: SimulationMode,
@@ -234,6 +249,7 @@ pub fn run_batch_simulations(
 }
 
 /// Genera reporte de simulaciones disponibles
+#[allow(dead_code)]
 pub fn generate_simulation_report(dir: &Path) -> Result<SimulationReport> {
     let simulations = scan_bend_simulations(dir)?;
 
@@ -259,6 +275,7 @@ pub fn generate_simulation_report(dir: &Path) -> Result<SimulationReport> {
     })
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 pub struct SimulationReport {
     pub total: usize,
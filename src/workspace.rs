@@ -12,7 +12,12 @@ use crate::analyzer::CodeAnalyzer;
 use crate::error::{MemoryPError, Result};
 use rayon::prelude::*;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Sufijo de los temporales de `write_atomic`, único dentro del proceso
+static NEXT_TMP: AtomicU64 = AtomicU64::new(0);
 // use std::sync::Mutex; // REMOVED as system is now lock-free
 
 // El bloqueo global fue removido para permitir escalado masivo 1M+ ops.
@@ -100,6 +105,7 @@ pub fn analyze_file(path: &Path) -> Result<String> {
 }
 
 /// Procesa múltiples archivos en paralelo para cualquier operación con bloqueo de seguridad
+#[allow(dead_code)]
 pub fn process_parallel<F>(paths: &[PathBuf], op: F) -> Result<Vec<Result<String>>>
 where
     F: Fn(&Path) -> Result<String> + Sync + Send,
@@ -138,7 +144,7 @@ pub fn edit_file(path: &Path) -> Result<String> {
     }
 
     if changed {
-        fs::write(path, &modified)?;
+        write_atomic(path, &modified)?;
         Ok(format!(
             "{}: ✨ NORMALIZADO (Zero-copy Buffering)",
             path.display()
@@ -149,7 +155,8 @@ pub fn edit_file(path: &Path) -> Result<String> {
 }
 
 /// Reparación inteligente optimizada (Import cleanup and EOL normalization)
-pub fn smart_repair(path: &Path) -> Result<String> {
+/// Con `dry_run` solo informa lo que repararía, sin escribir
+pub fn smart_repair(path: &Path, dry_run: bool) -> Result<String> {
     let content = fs::read_to_string(path)?;
     let mut seen_imports = std::collections::HashSet::new();
     let mut modified = String::with_capacity(content.len());
@@ -171,10 +178,13 @@ pub fn smart_repair(path: &Path) -> Result<String> {
     }
 
     if modified != content {
-        fs::write(path, &modified)?;
+        if !dry_run {
+            write_atomic(path, &modified)?;
+        }
         Ok(format!(
-            "{}: 🛠️ SMART REPAIR [ {} ]",
+            "{}: 🛠️ SMART REPAIR{} [ {} ]",
             path.display(),
+            if dry_run { " [DRY_RUN]" } else { "" },
             if changes.is_empty() {
                 "Formatting".to_string()
             } else {
@@ -209,7 +219,7 @@ pub fn repair_file(path: &Path) -> Result<String> {
 
     if changed {
         let result = new_lines.join("\n") + "\n";
-        fs::write(path, result)?;
+        write_atomic(path, result)?;
         Ok(format!(
             "{}: 🛠️ REPARADO (Espacios redundantes eliminados)",
             path.display()
@@ -218,3 +228,111 @@ pub fn repair_file(path: &Path) -> Result<String> {
         Ok(format!("{}: ✨ NO REQUIRIÓ REPARACIÓN", path.display()))
     }
 }
+
+/// Escribe en un temporal junto a `path` y lo renombra encima: si el proceso muere a mitad
+/// (señal, disco lleno) queda el archivo anterior intacto, nunca uno truncado.
+/// Sigue symlinks y conserva los permisos del original. Cada escritura usa su propio temporal
+/// (pid + contador), así dos escrituras simultáneas al mismo archivo no se mezclan.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let name = path.file_name().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "ruta sin nombre de archivo")
+    })?;
+    let tmp = path.with_file_name(format!(
+        ".{}.{}.{}.memory_p.tmp",
+        name.to_string_lossy(),
+        std::process::id(),
+        NEXT_TMP.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written = fs::File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(contents.as_ref())?;
+            // Los datos en disco antes del rename: tras un corte no queda un archivo vacío
+            file.sync_all()
+        })
+        .and_then(|_| match fs::metadata(&path) {
+            Ok(meta) => fs::set_permissions(&tmp, meta.permissions()),
+            Err(_) => Ok(()),
+        })
+        .and_then(|_| fs::rename(&tmp, &path));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+        return written;
+    }
+    sync_parent(&path);
+    Ok(())
+}
+
+/// Persiste la entrada del directorio tras el rename (en Windows no se puede abrir un
+/// directorio así; NTFS ya registra el rename en su journal)
+fn sync_parent(path: &Path) {
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        let _ = fs::File::open(dir).and_then(|d| d.sync_all());
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic_replaces_without_leftovers() {
+        let dir = std::env::temp_dir().join(format!("memory_p_atomic_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("lib.rs");
+        fs::write(&file, "viejo").unwrap();
+
+        write_atomic(&file, "nuevo").unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "nuevo");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        // Sin directorio padre no se toca nada
+        assert!(write_atomic(&dir.join("falta/lib.rs"), "x").is_err());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_concurrent_write_atomic_never_mixes_contents() {
+        let dir = std::env::temp_dir().join(format!("memory_p_race_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("lib.rs");
+        let writers: Vec<_> = (0..8u8)
+            .map(|i| {
+                let file = file.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..20 {
+                        write_atomic(&file, vec![b'a' + i; 64 * 1024]).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let contents = fs::read(&file).unwrap();
+        assert_eq!(contents.len(), 64 * 1024);
+        assert!(contents.iter().all(|&b| b == contents[0]));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_smart_repair_dry_run_leaves_file() {
+        let file = std::env::temp_dir().join(format!("memory_p_repair_{}.rs", std::process::id()));
+        let original = "use a;\nuse a;\nfn main() {}   \n";
+        fs::write(&file, original).unwrap();
+
+        let report = smart_repair(&file, true).unwrap();
+        assert!(report.contains("DRY_RUN") && report.contains("Duplicate import removed"));
+        assert_eq!(fs::read_to_string(&file).unwrap(), original);
+
+        smart_repair(&file, false).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "use a;\nfn main() {}\n");
+        fs::remove_file(&file).unwrap();
+    }
+}